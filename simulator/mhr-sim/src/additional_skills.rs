@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::data_manager::DataManager;
+use crate::data::deco_combination::DecorationCombination;
+use crate::full_equipments::FullEquipments;
+use crate::search::{check_free_slots, check_skill_levels, ResultFullEquipments};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AdditionalSkillsRequest {
+    #[serde(rename = "weaponSlots")]
+    pub weapon_slots: Vec<i32>,
+
+    #[serde(rename = "selectedSkills")]
+    pub selected_skills: HashMap<String, i32>,
+
+    /// Slots kept empty, as in the search of the result
+    #[serde(rename = "freeSlots")]
+    pub free_slots: Vec<i32>,
+
+    pub equipments: ResultFullEquipments,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AdditionalSkill {
+    pub id: String,
+
+    /// Highest total level reachable while keeping the selected skills
+    #[serde(rename = "maxLevel")]
+    pub max_level: i32,
+}
+
+/// Lists skills which could be added (or raised above the selected level)
+/// with leftover slots or by swapping decorations of the given result
+pub fn search_additional_skills(
+    dm: &DataManager,
+    request: &AdditionalSkillsRequest,
+) -> Result<Vec<AdditionalSkill>, String> {
+    check_skill_levels(dm, &request.selected_skills)?;
+    check_free_slots(&request.free_slots)?;
+
+    let full_equip =
+        FullEquipments::from_result(dm, request.weapon_slots.clone(), &request.equipments)?;
+
+    let armor_skills = &full_equip.all_skills;
+    let mut avail_slots = full_equip.avail_slots.clone();
+
+    if !DecorationCombination::is_possible_static_mut(
+        &mut avail_slots,
+        &mut request.free_slots.clone(),
+    ) {
+        return Err("Free slots don't fit in the equipments".to_string());
+    }
+
+    let avail_slots = &avail_slots;
+
+    let mut deco_req_skills = HashMap::<String, i32>::new();
+
+    for (id, &level) in &request.selected_skills {
+        let armor_level = *armor_skills.get(id).unwrap_or(&0);
+
+        if level <= armor_level {
+            continue;
+        }
+
+        if !dm.has_decoration(id) {
+            return Err(format!("Selected skill is not satisfied: {}", id));
+        }
+
+        deco_req_skills.insert(id.clone(), level - armor_level);
+    }
+
+    if !dm
+        .deco_combinations
+        .has_possible_combs(&deco_req_skills, avail_slots)
+    {
+        return Err("Selected skills are not satisfied".to_string());
+    }
+
+    let mut additional_skills = Vec::new();
+
+    for (id, skill) in &dm.skills {
+        let armor_level = *armor_skills.get(id).unwrap_or(&0);
+        let selected_level = *request.selected_skills.get(id).unwrap_or(&0);
+
+        let mut max_level = armor_level.max(selected_level).min(skill.max_level);
+
+        if dm.has_decoration(id) {
+            for level in max_level + 1..skill.max_level + 1 {
+                let mut req_skills = deco_req_skills.clone();
+                req_skills.insert(id.clone(), level - armor_level);
+
+                let is_possible = dm
+                    .deco_combinations
+                    .has_possible_combs(&req_skills, avail_slots);
+
+                if !is_possible {
+                    break;
+                }
+
+                max_level = level;
+            }
+        }
+
+        if selected_level < max_level {
+            additional_skills.push(AdditionalSkill {
+                id: id.clone(),
+                max_level,
+            });
+        }
+    }
+
+    additional_skills.sort_by(|skill1, skill2| skill1.id.cmp(&skill2.id));
+
+    Ok(additional_skills)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search_additional_skills, AdditionalSkill, AdditionalSkillsRequest};
+    use crate::{
+        data::armor::ArmorPart,
+        full_equipments::FullEquipments,
+        search::search,
+        test::{create_pinned_request, get_data_manager},
+    };
+
+    #[test]
+    fn additional_skills_of_pinned_result() {
+        let dm = get_data_manager();
+
+        let mut request = create_pinned_request(&[("critical_boost", 3), ("agitator", 3)]);
+
+        request
+            .constraints
+            .pinned_armors
+            .insert(ArmorPart::Waist, "silver_solcoil".to_string());
+
+        let result = search(dm, &request).unwrap();
+
+        assert_ne!(result.full_equipments.len(), 0);
+
+        let mut additional_request = AdditionalSkillsRequest {
+            weapon_slots: request.weapon_slots.clone(),
+            selected_skills: request.selected_skills.clone(),
+            free_slots: request.free_slots.clone(),
+            equipments: result.full_equipments[0].clone(),
+        };
+
+        let additional_skills = search_additional_skills(dm, &additional_request).unwrap();
+
+        // Windproof 3 is given by Silver Solhelm and critical boost is already maxed
+        assert!(additional_skills
+            .iter()
+            .any(|skill| skill.id == "windproof" && 3 <= skill.max_level));
+        assert!(additional_skills
+            .iter()
+            .all(|skill| skill.id != "critical_boost"));
+
+        // A free slot is kept empty, so it can't raise skills with decorations
+        additional_request.free_slots = vec![0, 0, 0, 1];
+
+        let with_free_slot = search_additional_skills(dm, &additional_request).unwrap();
+
+        let get_level_sum =
+            |skills: &Vec<AdditionalSkill>| skills.iter().map(|skill| skill.max_level).sum::<i32>();
+
+        assert!(with_free_slot.iter().all(|skill| additional_skills
+            .iter()
+            .any(|other| other.id == skill.id && skill.max_level <= other.max_level)));
+        assert!(get_level_sum(&with_free_slot) < get_level_sum(&additional_skills));
+
+        // Every slot kept free leaves none for the selected skills
+        additional_request.free_slots = FullEquipments::from_result(
+            dm,
+            request.weapon_slots.clone(),
+            &result.full_equipments[0],
+        )
+        .unwrap()
+        .avail_slots;
+
+        assert!(search_additional_skills(dm, &additional_request).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::armor::BaseArmor;
+
+/// Armors a search may pick, by their ownership
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArmorAvailability {
+    #[serde(rename = "ownedOnly")]
+    OwnedOnly,
+    #[serde(rename = "ownedPlusCraftable")]
+    OwnedPlusCraftable,
+    #[default]
+    #[serde(rename = "everything")]
+    Everything,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmorOwnership {
+    #[serde(rename = "owned")]
+    Owned,
+    /// Not crafted yet, but materials and unlocks are there
+    #[serde(rename = "craftable")]
+    Craftable,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OwnedArmor {
+    pub id: String,
+    pub ownership: ArmorOwnership,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ArmorInventory {
+    /// Ownership per base armor id, armors not listed are neither owned nor craftable
+    pub armors: HashMap<String, ArmorOwnership>,
+}
+
+impl ArmorInventory {
+    pub fn new(owned_armors: &Vec<OwnedArmor>) -> Self {
+        let mut armors = HashMap::new();
+
+        for owned in owned_armors {
+            armors.insert(owned.id.clone(), owned.ownership);
+        }
+
+        Self { armors }
+    }
+
+    pub fn set_ownership(&mut self, armor_id: &String, ownership: Option<ArmorOwnership>) {
+        match ownership {
+            Some(ownership) => {
+                self.armors.insert(armor_id.clone(), ownership);
+            }
+            None => {
+                self.armors.remove(armor_id);
+            }
+        }
+    }
+
+    /// Anomaly armors are always owned, as only owned armors can be anomaly crafted.
+    /// So is the empty armor of a part left bare.
+    pub fn is_owned(&self, armor: &BaseArmor) -> bool {
+        if BaseArmor::is_anomaly_armor(armor.id()) || BaseArmor::is_empty_armor(armor.id()) {
+            return true;
+        }
+
+        self.armors.get(armor.id()) == Some(&ArmorOwnership::Owned)
+    }
+
+    pub fn is_available(&self, armor: &BaseArmor, availability: ArmorAvailability) -> bool {
+        match availability {
+            ArmorAvailability::OwnedOnly => self.is_owned(armor),
+            ArmorAvailability::OwnedPlusCraftable => {
+                self.is_owned(armor) || self.armors.contains_key(armor.id())
+            }
+            ArmorAvailability::Everything => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ArmorAvailability, ArmorOwnership, OwnedArmor};
+    use crate::{
+        data::armor::{SexType, EMPTY_ARMOR_PREFIX},
+        search::{search, SearchRequest},
+        test::get_data_manager,
+    };
+
+    #[test]
+    fn armor_inventory_restricts_search() {
+        let mut dm = get_data_manager().clone();
+
+        let mut owned_armors = [
+            "silver_solhelm",
+            "lambent_hauberk",
+            "silver_solbraces",
+            "silver_solgreaves",
+        ]
+        .iter()
+        .map(|id| OwnedArmor {
+            id: id.to_string(),
+            ownership: ArmorOwnership::Owned,
+        })
+        .collect::<Vec<OwnedArmor>>();
+
+        owned_armors.push(OwnedArmor {
+            id: "silver_solcoil".to_string(),
+            ownership: ArmorOwnership::Craftable,
+        });
+
+        dm.set_armor_inventory(owned_armors);
+
+        let mut selected_skills = HashMap::<String, i32>::new();
+        selected_skills.insert("critical_boost".to_string(), 3);
+        selected_skills.insert("agitator".to_string(), 3);
+
+        let mut request = SearchRequest::new(
+            vec![3, 1, 0],
+            selected_skills,
+            vec![0, 0, 0, 0],
+            SexType::All,
+        );
+
+        request.constraints.armor_availability = ArmorAvailability::OwnedOnly;
+
+        let owned_only = search(&dm, &request).unwrap();
+
+        assert_ne!(owned_only.full_equipments.len(), 0);
+
+        for equip in &owned_only.full_equipments {
+            assert!(equip.armors.values().all(|armor| !armor.needs_crafting));
+            assert!(equip.armors["waist"].id.starts_with(EMPTY_ARMOR_PREFIX));
+        }
+
+        request.constraints.armor_availability = ArmorAvailability::OwnedPlusCraftable;
+
+        let craftable = search(&dm, &request).unwrap();
+
+        assert!(craftable.full_equipments.iter().any(|equip| {
+            let waist = &equip.armors["waist"];
+            waist.id == "silver_solcoil" && waist.needs_crafting
+        }));
+
+        dm.set_armor_ownership(&"silver_solcoil".to_string(), None)
+            .unwrap();
+
+        assert!(dm
+            .set_armor_ownership(&"not_existing".to_string(), Some(ArmorOwnership::Owned))
+            .is_err());
+        assert_eq!(
+            search(&dm, &request).unwrap().full_equipments.len(),
+            owned_only.full_equipments.len()
+        );
+    }
+}
@@ -0,0 +1,864 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::exit;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use log::{debug, info};
+
+use mhr_sim::data::armor::{ArmorPart, ArmorRank, SexType, StatType};
+use mhr_sim::data::data_manager::DataManager;
+use mhr_sim::data::data_override::{OverrideFile, OVERRIDE_DIRNAME};
+use mhr_sim::data::data_validation::DataReport;
+use mhr_sim::deco_placement::ResultDecorationSlot;
+use mhr_sim::export::{armor_name, deco_name, skill_name};
+use mhr_sim::full_equipments::FullEquipments;
+use mhr_sim::parse::CsvImport;
+use mhr_sim::search::Truncation;
+use mhr_sim::{
+    create_data_manager_with_overrides, encode_build, export_result_to_file, get_max_skill_levels,
+    load_data, parse_anomaly, parse_armor_inventory, parse_deco_inventory, parse_talisman, search,
+    search_additional_skills, AdditionalSkillsRequest, ArmorAvailability, DecoInventory,
+    ExportFormat, HunterProfile, SearchRequest, SearchResult,
+};
+
+#[derive(Clone, Debug, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum ExportFormatArg {
+    Json,
+    Csv,
+    Markdown,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum ArmorsArg {
+    OwnedOnly,
+    OwnedPlusCraftable,
+    Everything,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum RankArg {
+    Low,
+    High,
+    Master,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum SexArg {
+    Male,
+    Female,
+    Either,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Checks the data files with their overrides applied, exits with 1 on errors
+    Validate {
+        #[arg(long, value_enum, default_value = "table")]
+        format: OutputFormat,
+    },
+}
+
+/// Headless skillset search over the same data the simulator app uses
+#[derive(Parser, Debug)]
+#[command(name = "mhr-sim", version, subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Directory containing armor.json, skill.json and deco.json
+    #[arg(long, global = true, default_value = "./data")]
+    data_dir: PathBuf,
+
+    /// Override file patching armors, skills or decorations by id (kind by its name prefix),
+    /// applied after the ones in `<data dir>/overrides`, may be given multiple times
+    #[arg(long = "override", value_name = "FILE", global = true)]
+    overrides: Vec<PathBuf>,
+
+    /// Anomaly crafting list exported via mod (csv)
+    #[arg(long)]
+    anomaly: Option<String>,
+
+    /// Talisman list exported via mod (csv)
+    #[arg(long)]
+    talisman: Option<String>,
+
+    /// Language of names in the csv files (e.g. `ja`), names of any language are accepted if not set
+    #[arg(long)]
+    csv_lang: Option<String>,
+
+    /// Owned armors as lines of `<name>[,owned|craftable]` (csv)
+    #[arg(long)]
+    armor_inventory: Option<String>,
+
+    /// Armors to search by ownership in `--armor-inventory`
+    #[arg(long, value_enum, default_value = "everything")]
+    armors: ArmorsArg,
+
+    /// Owned decorations as lines of `<name>,<count>` (csv), limits the search to them
+    #[arg(long)]
+    deco_inventory: Option<String>,
+
+    /// Searches with any decoration and lists the ones missing from `--deco-inventory`
+    #[arg(long, requires = "deco_inventory")]
+    shopping_list: bool,
+
+    /// Requested skill as `<id or name>=<level>`, may be given multiple times
+    #[arg(short, long = "skill", value_name = "SKILL=LEVEL", required = true)]
+    skills: Vec<String>,
+
+    /// Hunter profile (json) providing sex type, language and default search options
+    #[arg(long)]
+    profile: Option<PathBuf>,
+
+    /// Weapon slot sizes, e.g. `3,1,0`, defaults to the profile's weapon slots
+    #[arg(long, value_delimiter = ',')]
+    weapon_slots: Option<Vec<i32>>,
+
+    /// Number of free slots to keep per slot size (1 to 4), e.g. `0,1,0,0`
+    #[arg(long, value_delimiter = ',', default_value = "0,0,0,0")]
+    free_slots: Vec<i32>,
+
+    /// Overrides the profile's sex type
+    #[arg(long, value_enum)]
+    sex: Option<SexArg>,
+
+    #[arg(long, value_enum, default_value = "table")]
+    format: OutputFormat,
+
+    /// Language used for names in table output, overrides the profile's language
+    #[arg(long)]
+    lang: Option<String>,
+
+    /// Also writes the results to a file, in the format of its extension (json, csv or md)
+    #[arg(long)]
+    export: Option<PathBuf>,
+
+    /// Format of the export file, overrides the one of its extension
+    #[arg(long, value_enum, requires = "export")]
+    export_format: Option<ExportFormatArg>,
+
+    /// Locks a part to an armor as `<part>=<armor id>`, may be given multiple times
+    #[arg(long = "pin", value_name = "PART=ARMOR")]
+    pins: Vec<String>,
+
+    /// Locks the talisman to a talisman id of the imported list
+    #[arg(long)]
+    pin_talisman: Option<String>,
+
+    /// Armor id to leave out of the search, may be given multiple times
+    #[arg(long = "exclude", value_name = "ARMOR")]
+    excluded_armors: Vec<String>,
+
+    /// Series (id of its first armor) to leave out of the search, may be given multiple times
+    #[arg(long = "exclude-series", value_name = "SERIES")]
+    excluded_series: Vec<String>,
+
+    /// Talisman id to leave out of the search, may be given multiple times
+    #[arg(long = "exclude-talisman", value_name = "TALISMAN")]
+    excluded_talismans: Vec<String>,
+
+    /// Armor rank to search, may be given multiple times, any rank if not given
+    #[arg(long = "rank", value_enum)]
+    ranks: Vec<RankArg>,
+
+    /// Lowest armor rarity to search, 0-based as in armor.json (in game rarity minus one)
+    #[arg(long)]
+    min_rarity: Option<i32>,
+
+    /// Highest armor rarity to search, 0-based as in armor.json
+    #[arg(long)]
+    max_rarity: Option<i32>,
+
+    /// Minimum total stat as `<stat>=<value>` (defense, fireRes, waterRes, iceRes, elecRes, dragonRes)
+    #[arg(long = "min-stat", value_name = "STAT=VALUE")]
+    min_stats: Vec<String>,
+
+    /// Maximum total stat as `<stat>=<value>`
+    #[arg(long = "max-stat", value_name = "STAT=VALUE")]
+    max_stats: Vec<String>,
+
+    /// Sorts results by the total stat in descending order
+    #[arg(long, value_name = "STAT")]
+    sort_by: Option<String>,
+
+    /// Lists skills still addable to each result with leftover slots (table output only)
+    #[arg(long)]
+    additional_skills: bool,
+
+    /// Prints a shareable build code per decoration combination (table output only)
+    #[arg(long)]
+    build_codes: bool,
+
+    /// Prints the highest level still reachable per skill instead of searching
+    #[arg(long)]
+    max_levels: bool,
+
+    /// Full equipments per page, overrides the profile's limit
+    #[arg(long)]
+    max_results: Option<usize>,
+
+    /// Armor combinations verified at most (best ones first), overrides the profile's limit
+    #[arg(long)]
+    max_candidates: Option<usize>,
+
+    /// Starts the page at the cursor printed with the previous page
+    #[arg(long)]
+    cursor: Option<usize>,
+}
+
+/// Exits if the file can't be read, invalid rows are only reported
+fn unwrap_csv_import<T>(filename: &str, result: Result<CsvImport<T>, String>) -> Vec<T> {
+    let csv_import = match result {
+        Ok(csv_import) => csv_import,
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(1);
+        }
+    };
+
+    for error in &csv_import.errors {
+        eprintln!("Skipped a row of {}, {}", filename, error);
+    }
+
+    csv_import.values
+}
+
+fn parse_skill_args(
+    skill_args: &Vec<String>,
+    dm: &DataManager,
+) -> Result<HashMap<String, i32>, String> {
+    let mut selected_skills = HashMap::new();
+
+    for skill_arg in skill_args {
+        let (name, level) = match skill_arg.rsplit_once('=') {
+            Some(pair) => pair,
+            None => return Err(format!("Invalid skill argument: {}", skill_arg)),
+        };
+
+        let level = match level.trim().parse::<i32>() {
+            Ok(level) => level,
+            Err(_) => return Err(format!("Invalid skill level: {}", skill_arg)),
+        };
+
+        let name = name.trim();
+
+        let skill_id = if dm.skills.contains_key(name) {
+            name.to_string()
+        } else {
+            let ids = dm.skill_name_dict.get_ids(None, name);
+
+            match ids.len() {
+                0 => return Err(format!("Unknown skill: {}", name)),
+                1 => ids[0].clone(),
+                _ => {
+                    return Err(format!(
+                        "Ambiguous skill name {}, use one of ids {:?}",
+                        name, ids
+                    ))
+                }
+            }
+        };
+
+        let max_level = dm.skills[&skill_id].max_level;
+
+        if level < 1 || max_level < level {
+            return Err(format!(
+                "Skill level out of range (1 - {}): {}",
+                max_level, skill_arg
+            ));
+        }
+
+        selected_skills.insert(skill_id, level);
+    }
+
+    Ok(selected_skills)
+}
+
+fn parse_pin_args(
+    pin_args: &Vec<String>,
+    dm: &DataManager,
+) -> Result<HashMap<ArmorPart, String>, String> {
+    let mut pinned_armors = HashMap::new();
+
+    for pin_arg in pin_args {
+        let (part_name, armor_id) = match pin_arg.split_once('=') {
+            Some(pair) => pair,
+            None => return Err(format!("Invalid pin argument: {}", pin_arg)),
+        };
+
+        let part = match ArmorPart::get_all()
+            .into_iter()
+            .find(|part| part.as_str() == part_name.trim())
+        {
+            Some(part) => part,
+            None => return Err(format!("Unknown armor part: {}", part_name)),
+        };
+
+        let armor_id = armor_id.trim();
+
+        let armor = if let Some(armor) = dm.armors.get(armor_id) {
+            armor
+        } else if let Some(anomaly) = dm.anomaly_armors.get(armor_id) {
+            &anomaly.affected
+        } else {
+            return Err(format!("Unknown armor: {}", armor_id));
+        };
+
+        if armor.part != part {
+            return Err(format!(
+                "Armor {} is not a {} armor",
+                armor_id,
+                part.as_str()
+            ));
+        }
+
+        pinned_armors.insert(part, armor_id.to_string());
+    }
+
+    Ok(pinned_armors)
+}
+
+fn parse_stat_type(name: &str) -> Result<StatType, String> {
+    match StatType::get_all()
+        .into_iter()
+        .find(|stat_type| stat_type.as_str() == name.trim())
+    {
+        Some(stat_type) => Ok(stat_type),
+        None => Err(format!("Unknown stat: {}", name)),
+    }
+}
+
+fn parse_stat_args(stat_args: &Vec<String>) -> Result<HashMap<StatType, i32>, String> {
+    let mut stats = HashMap::new();
+
+    for stat_arg in stat_args {
+        let (name, value) = match stat_arg.split_once('=') {
+            Some(pair) => pair,
+            None => return Err(format!("Invalid stat argument: {}", stat_arg)),
+        };
+
+        let value = match value.trim().parse::<i32>() {
+            Ok(value) => value,
+            Err(_) => return Err(format!("Invalid stat value: {}", stat_arg)),
+        };
+
+        stats.insert(parse_stat_type(name)?, value);
+    }
+
+    Ok(stats)
+}
+
+fn format_placement(dm: &DataManager, placement: &Vec<ResultDecorationSlot>, lang: &str) -> String {
+    let mut parts = Vec::<String>::new();
+
+    for slot in placement {
+        let deco = match &slot.deco_id {
+            Some(deco_id) => deco_name(dm, deco_id, lang),
+            None => "-".to_string(),
+        };
+
+        let text = format!("[{}] {}", slot.slot_size, deco);
+
+        if slot.slot_index == 0 {
+            parts.push(format!("{} {}", slot.part, text));
+        } else {
+            parts.last_mut().unwrap().push_str(&format!(" {}", text));
+        }
+    }
+
+    parts.join(" | ")
+}
+
+fn format_skills(dm: &DataManager, skills: &HashMap<String, i32>, lang: &str) -> String {
+    let mut skills = skills
+        .iter()
+        .map(|(id, level)| format!("{} {}", skill_name(dm, id, lang), level))
+        .collect::<Vec<String>>();
+
+    skills.sort();
+
+    skills.join(", ")
+}
+
+fn print_table(
+    dm: &DataManager,
+    request: &SearchRequest,
+    result: &SearchResult,
+    show_additional_skills: bool,
+    show_build_codes: bool,
+) {
+    let lang = request.lang.as_str();
+
+    let offset = request.cursor.unwrap_or(0);
+
+    for (index, full_equip) in result.full_equipments.iter().enumerate() {
+        println!("#{}", offset + index + 1);
+
+        for part in ArmorPart::get_all() {
+            let armor = match full_equip.armors.get(part.as_str()) {
+                Some(armor) => armor,
+                None => continue,
+            };
+
+            let name = armor_name(dm, &armor.base_id, lang);
+
+            let anomaly_mark = if armor.is_anomaly { " (anomaly)" } else { "" };
+
+            // Every armor needs crafting without an inventory
+            let craft_mark = if armor.needs_crafting && !dm.armor_inventory.armors.is_empty() {
+                " (craft)"
+            } else {
+                ""
+            };
+
+            println!(
+                "  {:<8} {}{}{} | slots: {:?} | {}",
+                part.as_str(),
+                name,
+                anomaly_mark,
+                craft_mark,
+                armor.slots,
+                format_skills(dm, &armor.skills, lang)
+            );
+        }
+
+        println!(
+            "  {:<8} slots: {:?} | {}",
+            ArmorPart::Talisman.as_str(),
+            full_equip.talisman.slots,
+            format_skills(dm, &full_equip.talisman.skills, lang)
+        );
+
+        let stat = &full_equip.stat;
+
+        println!(
+            "  {:<8} defense {} | fire {} | water {} | ice {} | elec {} | dragon {}",
+            "stat",
+            stat.defense,
+            stat.fire_res,
+            stat.water_res,
+            stat.ice_res,
+            stat.elec_res,
+            stat.dragon_res
+        );
+
+        for deco_comb in &full_equip.deco_combs {
+            let mut skills = deco_comb
+                .skills
+                .iter()
+                .map(|(id, slots)| format!("{} {:?}", skill_name(dm, id, lang), slots))
+                .collect::<Vec<String>>();
+
+            skills.sort();
+
+            println!(
+                "  {:<8} {:?} | {}",
+                "decos",
+                deco_comb.slots_sum,
+                skills.join(", ")
+            );
+
+            println!(
+                "  {:<8} {}",
+                "slots",
+                format_placement(dm, &deco_comb.placement, lang)
+            );
+
+            if !deco_comb.missing_decos.is_empty() {
+                let mut missing = deco_comb
+                    .missing_decos
+                    .iter()
+                    .map(|(id, count)| format!("{} x{}", deco_name(dm, id, lang), count))
+                    .collect::<Vec<String>>();
+
+                missing.sort();
+
+                println!("  {:<8} {}", "missing", missing.join(", "));
+            }
+
+            if show_build_codes {
+                let code =
+                    FullEquipments::from_result(dm, request.weapon_slots.clone(), full_equip)
+                        .and_then(|equipments| encode_build(dm, &equipments, &deco_comb.placement));
+
+                match code {
+                    Ok(code) => println!("  {:<8} {}", "code", code),
+                    Err(message) => println!("  {:<8} {}", "code", message),
+                }
+            }
+        }
+
+        if show_additional_skills {
+            let additional_request = AdditionalSkillsRequest {
+                weapon_slots: request.weapon_slots.clone(),
+                selected_skills: request.selected_skills.clone(),
+                free_slots: request.free_slots.clone(),
+                equipments: full_equip.clone(),
+            };
+
+            match search_additional_skills(dm, &additional_request) {
+                Ok(additional_skills) => {
+                    let skills = additional_skills
+                        .iter()
+                        .map(|skill| {
+                            format!("{} {}", skill_name(dm, &skill.id, lang), skill.max_level)
+                        })
+                        .collect::<Vec<String>>();
+
+                    println!("  {:<8} {}", "extra", skills.join(", "));
+                }
+                Err(message) => println!("  {:<8} {}", "extra", message),
+            }
+        }
+
+        println!();
+    }
+
+    println!("{} result(s)", result.full_equipments.len());
+}
+
+fn print_max_levels(dm: &DataManager, request: &SearchRequest) {
+    let max_levels = match get_max_skill_levels(dm, request) {
+        Ok(Some(max_levels)) => max_levels,
+        Ok(None) => {
+            eprintln!("Selected skills are not possible");
+            exit(1);
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(2);
+        }
+    };
+
+    let mut lines = max_levels
+        .iter()
+        .filter(|(_, level)| 0 < **level)
+        .map(|(id, level)| {
+            let max_level = dm.skills[id].max_level;
+
+            format!(
+                "{:<30} {} / {}",
+                skill_name(dm, id, &request.lang),
+                level,
+                max_level
+            )
+        })
+        .collect::<Vec<String>>();
+
+    lines.sort();
+
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+fn print_data_report(report: &DataReport, format: &OutputFormat) {
+    if let OutputFormat::Json = format {
+        println!("{}", serde_json::to_string_pretty(report).unwrap());
+        return;
+    }
+
+    println!(
+        "{} armors, {} skills, {} decorations",
+        report.armor_count, report.skill_count, report.deco_count
+    );
+
+    for issue in &report.issues {
+        println!("{}", issue);
+    }
+
+    println!(
+        "{} error(s), {} warning(s)",
+        report.get_errors().len(),
+        report.issues.len() - report.get_errors().len()
+    );
+}
+
+fn main() {
+    env_logger::init();
+
+    let args = Args::parse();
+
+    let data_file = |name: &str| args.data_dir.join(name).to_string_lossy().to_string();
+
+    let mut override_files = OverrideFile::find_in_dir(&args.data_dir.join(OVERRIDE_DIRNAME));
+
+    for filename in &args.overrides {
+        match OverrideFile::from_filename(filename) {
+            Some(override_file) => override_files.push(override_file),
+            None => {
+                eprintln!(
+                    "Override file name {} should start with armor, skill or deco and end with .json",
+                    filename.display()
+                );
+                exit(2);
+            }
+        }
+    }
+
+    if let Some(Command::Validate { format }) = &args.command {
+        let loaded = match load_data(
+            &data_file("armor.json"),
+            &data_file("skill.json"),
+            &data_file("deco.json"),
+            &override_files,
+        ) {
+            Ok(loaded) => loaded,
+            Err(message) => {
+                eprintln!("{}", message);
+                exit(1);
+            }
+        };
+
+        print_data_report(&loaded.report, format);
+
+        if loaded.report.has_errors() {
+            exit(1);
+        }
+
+        return;
+    }
+
+    let (mut dm, report) = match create_data_manager_with_overrides(
+        &data_file("armor.json"),
+        &data_file("skill.json"),
+        &data_file("deco.json"),
+        &override_files,
+    ) {
+        Ok(loaded) => loaded,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("Run `mhr-sim validate` for the full report");
+            exit(1);
+        }
+    };
+
+    // Skipped overrides were asked for, other warnings are in `mhr-sim validate`
+    for issue in &report.issues {
+        if issue.kind == "override" {
+            eprintln!("Override skipped: {}", issue);
+        } else {
+            info!("{}", issue);
+        }
+    }
+
+    for collision in dm.get_name_collisions() {
+        info!(
+            "Ambiguous {} name '{}' ({}): {:?}",
+            collision.kind,
+            collision.name,
+            collision.lang.as_deref().unwrap_or("across languages"),
+            collision.ids
+        );
+    }
+
+    let csv_lang = args.csv_lang.as_deref();
+
+    if let Some(filename) = &args.anomaly {
+        let anomalies = parse_anomaly(
+            filename,
+            &dm.armors,
+            &dm.armor_name_dict,
+            &dm.skill_name_dict,
+            csv_lang,
+        );
+
+        dm.set_anomalies(unwrap_csv_import(filename, anomalies));
+    }
+
+    if let Some(filename) = &args.talisman {
+        let talismans = parse_talisman(filename, &dm.skill_name_dict, csv_lang);
+
+        dm.set_talismans(unwrap_csv_import(filename, talismans));
+    }
+
+    if let Some(filename) = &args.armor_inventory {
+        let owned_armors = parse_armor_inventory(filename, &dm.armor_name_dict, csv_lang);
+
+        dm.set_armor_inventory(unwrap_csv_import(filename, owned_armors));
+    }
+
+    debug!(
+        "Anomaly armor count: {}, talisman count: {}",
+        dm.anomaly_armors.len(),
+        dm.talismans.len()
+    );
+
+    let selected_skills = match parse_skill_args(&args.skills, &dm) {
+        Ok(skills) => skills,
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(2);
+        }
+    };
+
+    let profile = match &args.profile {
+        Some(filename) => HunterProfile::load(filename),
+        None => HunterProfile::default(),
+    };
+
+    let mut request = profile.create_request(
+        args.weapon_slots.clone(),
+        selected_skills,
+        args.free_slots.clone(),
+    );
+
+    if let Some(sex) = &args.sex {
+        request.sex_type = match sex {
+            SexArg::Male => SexType::Male,
+            SexArg::Female => SexType::Female,
+            SexArg::Either => SexType::All,
+        };
+    }
+
+    if let Some(lang) = &args.lang {
+        request.lang = lang.clone();
+    }
+
+    request.constraints.pinned_armors = match parse_pin_args(&args.pins, &dm) {
+        Ok(pinned_armors) => pinned_armors,
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(2);
+        }
+    };
+
+    if let Some(filename) = &args.deco_inventory {
+        let owned_decos = parse_deco_inventory(filename, &dm.deco_name_dict, csv_lang);
+
+        request.deco_inventory = Some(DecoInventory::new(
+            &unwrap_csv_import(filename, owned_decos),
+            args.shopping_list,
+        ));
+    }
+
+    request.constraints.armor_availability = match args.armors {
+        ArmorsArg::OwnedOnly => ArmorAvailability::OwnedOnly,
+        ArmorsArg::OwnedPlusCraftable => ArmorAvailability::OwnedPlusCraftable,
+        ArmorsArg::Everything => ArmorAvailability::Everything,
+    };
+    request.constraints.ranks = args
+        .ranks
+        .iter()
+        .map(|rank| match rank {
+            RankArg::Low => ArmorRank::Low,
+            RankArg::High => ArmorRank::High,
+            RankArg::Master => ArmorRank::Master,
+        })
+        .collect();
+    request.constraints.min_rarity = args.min_rarity;
+    request.constraints.max_rarity = args.max_rarity;
+    request.constraints.pinned_talisman = args.pin_talisman.clone();
+    request.constraints.excluded_armors = args.excluded_armors.iter().cloned().collect();
+    request.constraints.excluded_series = args.excluded_series.iter().cloned().collect();
+    request.constraints.excluded_talismans = args.excluded_talismans.iter().cloned().collect();
+
+    let stat_limits = parse_stat_args(&args.min_stats).and_then(|min_stats| {
+        let max_stats = parse_stat_args(&args.max_stats)?;
+        let sort_by = match &args.sort_by {
+            Some(name) => Some(parse_stat_type(name)?),
+            None => None,
+        };
+
+        Ok((min_stats, max_stats, sort_by))
+    });
+
+    match stat_limits {
+        Ok((min_stats, max_stats, sort_by)) => {
+            request.constraints.min_stats = min_stats;
+            request.constraints.max_stats = max_stats;
+            request.sort_by = sort_by;
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(2);
+        }
+    }
+
+    if let Some(max_results) = args.max_results {
+        request.limits.max_results = max_results;
+    }
+
+    if let Some(max_candidates) = args.max_candidates {
+        request.limits.max_candidates = max_candidates;
+    }
+
+    request.cursor = args.cursor;
+
+    if args.max_levels {
+        print_max_levels(&dm, &request);
+        return;
+    }
+
+    let result = match search(&dm, &request) {
+        Ok(result) => result,
+        Err(message) => {
+            eprintln!("{}", message);
+            exit(2);
+        }
+    };
+
+    eprintln!("{}", result.log.trim());
+
+    match args.format {
+        OutputFormat::Table => {
+            print_table(
+                &dm,
+                &request,
+                &result,
+                args.additional_skills,
+                args.build_codes,
+            );
+            print_truncations(&result);
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result).unwrap()),
+    }
+
+    if let Some(filename) = &args.export {
+        let format = match &args.export_format {
+            Some(ExportFormatArg::Json) => Some(ExportFormat::Json),
+            Some(ExportFormatArg::Csv) => Some(ExportFormat::Csv),
+            Some(ExportFormatArg::Markdown) => Some(ExportFormat::Markdown),
+            None => ExportFormat::from_filename(filename),
+        };
+
+        let exported = match format {
+            Some(format) => export_result_to_file(&dm, &result, &request.lang, format, filename),
+            None => Err(format!(
+                "Unknown export format of {:?}, use --export-format",
+                filename
+            )),
+        };
+
+        if let Err(message) = exported {
+            eprintln!("{}", message);
+            exit(1);
+        }
+    }
+}
+
+fn print_truncations(result: &SearchResult) {
+    for truncation in &result.truncations {
+        match truncation {
+            Truncation::CandidateLimit { limit, total } => eprintln!(
+                "Truncated: only the best {} of {} candidates were verified, raise --max-candidates for the rest",
+                limit, total
+            ),
+            Truncation::ResultLimit { limit, offset } => eprintln!(
+                "Truncated: showing {} results from #{}",
+                limit,
+                offset + 1
+            ),
+        }
+    }
+
+    if let Some(cursor) = result.next_cursor {
+        eprintln!("Next page: --cursor {}", cursor);
+    }
+}
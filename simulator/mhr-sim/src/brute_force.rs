@@ -0,0 +1,577 @@
+use std::collections::HashMap;
+
+use itertools::iproduct;
+
+use crate::data::armor::{ArmorPart, ArmorStat, BaseArmor, Talisman};
+use crate::data::data_manager::DataManager;
+use crate::data::deco::Decoration;
+use crate::data::skill::MAX_SLOT_LEVEL;
+use crate::search::{ResultFullEquipments, SearchRequest};
+
+/// Full equipments satisfying a request, found by `brute_force_search`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BruteForceSet {
+    /// Armor ids in `ArmorPart::get_all` order (empty armor ids for no armor), then the talisman id
+    pub ids: Vec<String>,
+    /// Decorations of one assignment completing the skills, sorted by id
+    pub deco_ids: Vec<String>,
+}
+
+/// An armor or talisman as seen by the brute force search
+struct Piece<'a> {
+    id: &'a String,
+    skills: HashMap<&'a String, i32>,
+    slot_sizes: Vec<i32>,
+    stat: Option<&'a ArmorStat>,
+}
+
+/// Every armor and talisman combination with every decoration assignment, without any pruning.
+/// Slow beyond a handful of armors per part, meant as a reference for `search` on small data.
+///
+/// Follows the sex type, constraints, free slots and owned decorations of the request,
+/// but not its limits, cursor or sorting.
+pub fn brute_force_search(dm: &DataManager, request: &SearchRequest) -> Vec<BruteForceSet> {
+    let constraints = &request.constraints;
+
+    let mut pieces_by_part = Vec::new();
+
+    for part in ArmorPart::get_all() {
+        let mut pieces = Vec::new();
+
+        for armor in dm.get_parts(part.clone()) {
+            let original = match dm.get_anomaly_armor(armor.id()) {
+                Some(anomaly) => &anomaly.original,
+                None => armor,
+            };
+
+            if armor.sex_type.is_wearable_by(&request.sex_type)
+                && constraints.allows_armor(armor, original, &dm.armor_inventory)
+            {
+                pieces.push(armor_piece(armor));
+            }
+        }
+
+        if !constraints.is_pinned(&part) {
+            pieces.push(armor_piece(&dm.empty_armors[&part]));
+        }
+
+        pieces_by_part.push(pieces);
+    }
+
+    let mut talismans = dm
+        .talismans
+        .iter()
+        .filter(|tali| constraints.allows_talisman(tali))
+        .map(talisman_piece)
+        .collect::<Vec<Piece>>();
+
+    if !constraints.is_pinned(&ArmorPart::Talisman) {
+        talismans.push(talisman_piece(&dm.empty_talisman));
+    }
+
+    let mut sets = Vec::new();
+
+    for (helm, torso, arm, waist, feet, tali) in iproduct!(
+        &pieces_by_part[0],
+        &pieces_by_part[1],
+        &pieces_by_part[2],
+        &pieces_by_part[3],
+        &pieces_by_part[4],
+        &talismans
+    ) {
+        let pieces = vec![helm, torso, arm, waist, feet, tali];
+
+        if constraints.has_stat_limits() {
+            let mut total_stat = ArmorStat::default();
+
+            for stat in pieces.iter().filter_map(|piece| piece.stat) {
+                total_stat.add(stat);
+            }
+
+            if !constraints.allows_stat(&total_stat) {
+                continue;
+            }
+        }
+
+        if let Some(deco_ids) = find_decorations(dm, request, &pieces) {
+            sets.push(BruteForceSet {
+                ids: pieces.iter().map(|piece| piece.id.clone()).collect(),
+                deco_ids,
+            });
+        }
+    }
+
+    sets
+}
+
+/// Ids of a search result in the order of `BruteForceSet::ids`
+pub fn get_result_ids(result: &ResultFullEquipments) -> Vec<String> {
+    let mut ids = ArmorPart::get_all()
+        .iter()
+        .map(|part| result.armors[part.as_str()].id.clone())
+        .collect::<Vec<String>>();
+
+    ids.push(result.talisman.id.clone());
+
+    ids
+}
+
+fn armor_piece(armor: &BaseArmor) -> Piece<'_> {
+    Piece {
+        id: armor.id(),
+        skills: armor
+            .skills
+            .iter()
+            .map(|(id, skill)| (id, skill.level))
+            .collect(),
+        slot_sizes: armor.slots.clone(),
+        stat: Some(&armor.stat),
+    }
+}
+
+fn talisman_piece(tali: &Talisman) -> Piece<'_> {
+    Piece {
+        id: tali.id(),
+        skills: tali
+            .skills
+            .iter()
+            .map(|skill| (&skill.id, skill.level))
+            .collect(),
+        slot_sizes: tali.slot_sizes.clone(),
+        stat: None,
+    }
+}
+
+/// Decorations completing the skills left by the equipments, `None` if there are none
+fn find_decorations(
+    dm: &DataManager,
+    request: &SearchRequest,
+    pieces: &Vec<&Piece>,
+) -> Option<Vec<String>> {
+    let mut req_skills = request.selected_skills.clone();
+
+    for piece in pieces {
+        for (skill_id, level) in &piece.skills {
+            if let Some(req_level) = req_skills.get_mut(*skill_id) {
+                *req_level -= level;
+            }
+        }
+    }
+
+    req_skills.retain(|_, level| 0 < *level);
+
+    // Decorations of other skills never help
+    let mut decos = Vec::new();
+
+    for skill_id in req_skills.keys() {
+        let skill_decos = dm.get_deco_by_skill_id(skill_id);
+
+        if skill_decos.is_empty() {
+            return None;
+        }
+
+        decos.extend(skill_decos);
+    }
+
+    decos.sort_by(|deco1, deco2| deco1.id.cmp(&deco2.id));
+
+    // Empty slot counts per slot size, slots of the same size are interchangeable
+    let mut slot_counts = vec![0; MAX_SLOT_LEVEL];
+
+    let weapon_slots = request.weapon_slots.iter();
+    let piece_slots = pieces.iter().flat_map(|piece| piece.slot_sizes.iter());
+
+    for slot_size in weapon_slots.chain(piece_slots) {
+        if 0 < *slot_size {
+            slot_counts[*slot_size as usize - 1] += 1;
+        }
+    }
+
+    let mut used = Vec::new();
+
+    if assign_slot_size(
+        request,
+        &decos,
+        MAX_SLOT_LEVEL,
+        &mut slot_counts,
+        &mut req_skills,
+        &mut used,
+    ) {
+        let mut deco_ids = used
+            .iter()
+            .map(|deco: &&Decoration| deco.id.clone())
+            .collect::<Vec<String>>();
+        deco_ids.sort();
+
+        return Some(deco_ids);
+    }
+
+    None
+}
+
+/// Tries every multiset of decorations for the slots of `slot_size`, then the smaller slots
+fn assign_slot_size<'a>(
+    request: &SearchRequest,
+    decos: &Vec<&'a Decoration>,
+    slot_size: usize,
+    slot_counts: &mut Vec<i32>,
+    req_skills: &mut HashMap<String, i32>,
+    used: &mut Vec<&'a Decoration>,
+) -> bool {
+    if req_skills.values().all(|level| *level <= 0) {
+        return has_free_slots(slot_counts, &request.free_slots);
+    }
+
+    if slot_size == 0 {
+        return false;
+    }
+
+    assign_deco(request, decos, 0, slot_size, slot_counts, req_skills, used)
+}
+
+/// Puts any count of `decos[deco_index]` into the slots of `slot_size`, then the next decorations
+fn assign_deco<'a>(
+    request: &SearchRequest,
+    decos: &Vec<&'a Decoration>,
+    deco_index: usize,
+    slot_size: usize,
+    slot_counts: &mut Vec<i32>,
+    req_skills: &mut HashMap<String, i32>,
+    used: &mut Vec<&'a Decoration>,
+) -> bool {
+    if deco_index == decos.len() {
+        return assign_slot_size(request, decos, slot_size - 1, slot_counts, req_skills, used);
+    }
+
+    let deco = decos[deco_index];
+
+    if assign_deco(
+        request,
+        decos,
+        deco_index + 1,
+        slot_size,
+        slot_counts,
+        req_skills,
+        used,
+    ) {
+        return true;
+    }
+
+    if (slot_size as i32) < deco.slot_size {
+        return false;
+    }
+
+    let mut placed = 0;
+    let mut found = false;
+
+    while 0 < slot_counts[slot_size - 1] && 0 < req_skills[&deco.skill_id] {
+        if let Some(inventory) = &request.deco_inventory {
+            let used_count = used.iter().filter(|used| used.id == deco.id).count() as i32;
+
+            if inventory.limits_search() && inventory.get_count(&deco.id) <= used_count {
+                break;
+            }
+        }
+
+        slot_counts[slot_size - 1] -= 1;
+        *req_skills.get_mut(&deco.skill_id).unwrap() -= deco.skill_level;
+        used.push(deco);
+        placed += 1;
+
+        if assign_deco(
+            request,
+            decos,
+            deco_index + 1,
+            slot_size,
+            slot_counts,
+            req_skills,
+            used,
+        ) {
+            found = true;
+            break;
+        }
+    }
+
+    if found {
+        return true;
+    }
+
+    for _ in 0..placed {
+        slot_counts[slot_size - 1] += 1;
+        *req_skills.get_mut(&deco.skill_id).unwrap() += deco.skill_level;
+        used.pop();
+    }
+
+    false
+}
+
+/// Each free slot of size `n + 1` (`free_slots[n]` of them) needs an empty slot at least as large
+fn has_free_slots(slot_counts: &[i32], free_slots: &[i32]) -> bool {
+    let mut empty_larger = 0;
+    let mut free_larger = 0;
+
+    for slot_size_index in (0..MAX_SLOT_LEVEL).rev() {
+        empty_larger += slot_counts[slot_size_index];
+        free_larger += free_slots.get(slot_size_index).cloned().unwrap_or(0);
+
+        if empty_larger < free_larger {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::{brute_force_search, get_result_ids};
+    use crate::{
+        data::{
+            armor::{AnomalyArmor, ArmorPart, ArmorStat, SexType, Talisman, TalismanSkill},
+            data_manager::DataManager,
+        },
+        parse::load_data,
+        search::{search, SearchRequest},
+    };
+
+    /// Small xorshift generator, so generated cases are the same on every run
+    struct TestRng(u64);
+
+    impl TestRng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Levels of the requested skills then slot sizes of an armor or talisman,
+    /// which is as good as another one for the request if none of them is lower
+    fn get_equipment_key(dm: &DataManager, skill_ids: &[String], id: &String) -> Vec<i32> {
+        let (skills, mut slots) = match dm.get_armor_by_id(id) {
+            Some((armor, _)) => (
+                armor
+                    .skills
+                    .iter()
+                    .map(|(id, skill)| (id.clone(), skill.level))
+                    .collect::<HashMap<String, i32>>(),
+                armor.slots.clone(),
+            ),
+            None => {
+                let tali = dm.get_talisman_by_id(id).unwrap();
+
+                (
+                    tali.skills
+                        .iter()
+                        .map(|skill| (skill.id.clone(), skill.level))
+                        .collect(),
+                    tali.slot_sizes.clone(),
+                )
+            }
+        };
+
+        slots.resize(3, 0);
+        slots.sort_by(|a, b| b.cmp(a));
+
+        let mut key = skill_ids
+            .iter()
+            .map(|skill_id| *skills.get(skill_id).unwrap_or(&0))
+            .collect::<Vec<i32>>();
+        key.append(&mut slots);
+
+        key
+    }
+
+    #[test]
+    fn search_matches_brute_force() {
+        let data = load_data(
+            "../src-tauri/data/armor.json",
+            "../src-tauri/data/skill.json",
+            "../src-tauri/data/deco.json",
+            &Vec::new(),
+        )
+        .unwrap();
+
+        let skills = data
+            .skills
+            .iter()
+            .map(|skill| (skill.id.clone(), skill.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let mut rng = TestRng(0x9e37_79b9_7f4a_7c15);
+        let mut compared_count = 0;
+
+        for case in 0..24 {
+            // A few armors per part, sharing skills often enough to complete requests
+            let mut armors = HashMap::new();
+
+            for part in ArmorPart::get_all() {
+                let part_armors = data
+                    .armors
+                    .iter()
+                    .filter(|armor| armor.part == part && !armor.skills.is_empty())
+                    .collect::<Vec<_>>();
+
+                for _ in 0..3 {
+                    let armor = part_armors[rng.below(part_armors.len())];
+                    armors.insert(armor.id().clone(), armor.clone());
+                }
+            }
+
+            let mut skill_pool = armors
+                .values()
+                .flat_map(|armor| armor.skills.keys().cloned())
+                .collect::<Vec<String>>();
+            skill_pool.sort();
+            skill_pool.dedup();
+
+            let mut selected_skills = HashMap::new();
+
+            for _ in 0..3 {
+                let skill_id = &skill_pool[rng.below(skill_pool.len())];
+                let level = 1 + rng.below(skills[skill_id].max_level.min(5) as usize);
+
+                selected_skills.insert(skill_id.clone(), level as i32);
+            }
+
+            let mut armor_ids = armors.keys().cloned().collect::<Vec<String>>();
+            armor_ids.sort();
+
+            // Decorations of other skills are never used, and slow down creating the data manager
+            let decos = data
+                .decos
+                .iter()
+                .filter(|deco| skill_pool.contains(&deco.skill_id))
+                .map(|deco| (deco.id.clone(), deco.clone()))
+                .collect();
+
+            let mut dm = DataManager::new(armors, skills.clone(), decos).unwrap();
+
+            // Anomalies with larger slots, and talismans with requested skills
+            let mut anomalies = Vec::new();
+
+            for _ in 0..2 {
+                let armor = dm.armors[&armor_ids[rng.below(armor_ids.len())]].clone();
+                let mut anomaly =
+                    AnomalyArmor::new(armor, ArmorStat::default(), Vec::new(), HashMap::new());
+
+                let slot_index = rng.below(3);
+                anomaly.affected.slots[slot_index] =
+                    (anomaly.affected.slots[slot_index] + 2).min(4);
+                anomalies.push(anomaly);
+            }
+
+            dm.set_anomalies(anomalies);
+
+            let requested = selected_skills.keys().cloned().collect::<Vec<String>>();
+            let talismans = (0..3)
+                .map(|index| {
+                    Talisman::new(
+                        format!("talisman_{}", index),
+                        vec![TalismanSkill {
+                            id: requested[rng.below(requested.len())].clone(),
+                            level: 1 + rng.below(2) as i32,
+                        }],
+                        vec![rng.below(4) as i32, rng.below(2) as i32, 0],
+                    )
+                })
+                .collect();
+
+            dm.set_talismans(talismans);
+
+            let weapon_slots = vec![rng.below(5) as i32, rng.below(3) as i32, 0];
+            let mut free_slots = vec![0, 0, 0, 0];
+
+            if case % 4 == 3 {
+                free_slots[rng.below(2)] = 1;
+            }
+
+            let mut request =
+                SearchRequest::new(weapon_slots, selected_skills, free_slots, SexType::All);
+            request.limits.max_candidates = usize::MAX;
+            request.limits.max_results = usize::MAX;
+
+            let result = search(&dm, &request).unwrap();
+            let expected = brute_force_search(&dm, &request);
+
+            assert_eq!(result.truncations, Vec::new());
+
+            let result_ids = result
+                .full_equipments
+                .iter()
+                .map(get_result_ids)
+                .collect::<Vec<Vec<String>>>();
+            let unique_ids = result_ids.iter().collect::<HashSet<&Vec<String>>>();
+            let expected_ids = expected
+                .iter()
+                .map(|set| &set.ids)
+                .collect::<HashSet<&Vec<String>>>();
+
+            assert_eq!(
+                unique_ids.len(),
+                result_ids.len(),
+                "Case {}: duplicate sets",
+                case
+            );
+
+            for ids in &result_ids {
+                assert!(
+                    expected_ids.contains(ids),
+                    "Case {}: invalid set {:?} for {:?}",
+                    case,
+                    ids,
+                    request
+                );
+            }
+
+            let mut skill_ids = request.selected_skills.keys().cloned().collect::<Vec<_>>();
+            skill_ids.sort();
+
+            let mut keys = HashMap::new();
+            let mut get_keys = |ids: &Vec<String>| {
+                ids.iter()
+                    .map(|id| {
+                        keys.entry(id.clone())
+                            .or_insert_with(|| get_equipment_key(&dm, &skill_ids, id))
+                            .clone()
+                    })
+                    .collect::<Vec<Vec<i32>>>()
+            };
+
+            let result_keys = result_ids.iter().map(&mut get_keys).collect::<Vec<_>>();
+
+            // Pruning may leave out sets whose every equipment has an equal or better one in a result
+            for set in &expected {
+                if unique_ids.contains(&set.ids) {
+                    continue;
+                }
+
+                let set_keys = get_keys(&set.ids);
+
+                let is_covered = result_keys.iter().any(|equip_keys| {
+                    equip_keys.iter().zip(set_keys.iter()).all(|(key, other)| {
+                        key.iter()
+                            .zip(other.iter())
+                            .all(|(value, other)| other <= value)
+                    })
+                });
+
+                assert!(
+                    is_covered,
+                    "Case {}: missing set {:?} (decorations {:?}) for {:?}",
+                    case, set.ids, set.deco_ids, request
+                );
+            }
+
+            if !expected.is_empty() {
+                compared_count += 1;
+            }
+        }
+
+        assert!(12 <= compared_count);
+    }
+}
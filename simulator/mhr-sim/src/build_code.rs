@@ -0,0 +1,583 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::calc::armor::CalcArmor;
+use crate::calc::calc_equipment::CalcEquipment;
+use crate::calc::talisman::CalcTalisman;
+use crate::data::armor::{
+    AnomalyArmor, ArmorPart, ArmorSkill, ArmorStat, BaseArmor, Talisman, TalismanSkill,
+    ANOMALY_ARMOR_PREFIX,
+};
+use crate::data::data_manager::DataManager;
+use crate::data::skill::MAX_SLOT_LEVEL;
+use crate::deco_placement::{get_all_slots, ResultDecorationSlot};
+use crate::full_equipments::FullEquipments;
+use crate::BoxCalcEquipment;
+
+/// Version written in front of every code, bumped on any change of `BuildCode`
+pub static BUILD_CODE_VERSION: u32 = 1;
+
+pub static SHARED_TALISMAN_ID: &str = "shared_talisman";
+
+/// Decoded codes larger than this are rejected
+const MAX_BUILD_CODE_LEN: u64 = 64 * 1024;
+
+/// Content of a build code, compressed and base64 (url safe) encoded.
+/// Anomaly armors and talismans are embedded by their stats, as their ids only make sense
+/// with the files of who shared the build.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BuildCode {
+    #[serde(rename = "weaponSlots")]
+    weapon_slots: Vec<i32>,
+    /// Helm to feet, `None` for parts left bare
+    armors: Vec<Option<BuildArmor>>,
+    talisman: BuildTalisman,
+    decos: Vec<BuildDeco>,
+    /// Skill levels the build had when shared, to detect data changes since then
+    skills: BTreeMap<String, i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BuildArmor {
+    /// Base armor id, the original armor of anomaly ones
+    id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    anomaly: Option<BuildAnomaly>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BuildAnomaly {
+    stat: ArmorStat,
+    slots: Vec<i32>,
+    skills: BTreeMap<String, i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BuildTalisman {
+    skills: Vec<(String, i32)>,
+    #[serde(rename = "slotSizes")]
+    slot_sizes: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BuildDeco {
+    part: String,
+    #[serde(rename = "slotIndex")]
+    slot_index: usize,
+    id: String,
+}
+
+/// Build rebuilt from a code, owning the anomaly armors and talisman it embeds
+#[derive(Serialize, Debug, Clone)]
+pub struct DecodedBuild {
+    pub weapon_slots: Vec<i32>,
+    /// Ids of the armors worn from helm to feet, original armor ids of anomaly ones
+    pub armor_ids: Vec<String>,
+    /// Anomaly armors rebuilt from the augments in the code, by part
+    pub anomalies: HashMap<ArmorPart, AnomalyArmor>,
+    pub talisman: Talisman,
+    /// Every slot of the build with the decoration in it
+    pub placement: Vec<ResultDecorationSlot>,
+    /// Skill levels with decorations, not capped to max levels
+    pub skills: HashMap<String, i32>,
+}
+
+impl DecodedBuild {
+    pub fn full_equipments<'a>(&'a self, dm: &'a DataManager) -> FullEquipments<'a> {
+        let mut equipments = Vec::<BoxCalcEquipment<'a>>::new();
+
+        for (part, armor_id) in ArmorPart::get_all().iter().zip(&self.armor_ids) {
+            let calc_armor = match self.anomalies.get(part) {
+                Some(anomaly) => CalcArmor::<'a>::new_anomaly(&anomaly.affected, &anomaly.original),
+                // Checked to exist when decoded
+                None => CalcArmor::<'a>::new(dm.get_armor_by_id(armor_id).unwrap().0),
+            };
+
+            equipments.push(calc_armor.clone_dyn());
+        }
+
+        equipments.push(CalcTalisman::<'a>::new(&self.talisman).clone_dyn());
+
+        FullEquipments::new(self.weapon_slots.clone(), equipments)
+    }
+}
+
+/// Encodes the equipments with decorations as placed in `placement`
+pub fn encode_build(
+    dm: &DataManager,
+    full_equipments: &FullEquipments,
+    placement: &Vec<ResultDecorationSlot>,
+) -> Result<String, String> {
+    let mut armors = Vec::new();
+
+    for part in ArmorPart::get_all() {
+        let armor = match full_equipments
+            .equipments
+            .iter()
+            .find(|equipment| equipment.part() == &part)
+        {
+            Some(equipment) => equipment.as_armor(),
+            None => {
+                armors.push(None);
+                continue;
+            }
+        };
+
+        if BaseArmor::is_empty_armor(armor.id()) {
+            armors.push(None);
+            continue;
+        }
+
+        if BaseArmor::is_slot_armor(armor.id()) {
+            return Err(format!("Armor {} is not a real armor", armor.id()));
+        }
+
+        let anomaly = if armor.is_anomaly() {
+            let anomaly = match dm.get_anomaly_armor(armor.id()) {
+                Some(anomaly) => anomaly,
+                None => return Err(format!("Unknown anomaly armor: {}", armor.id())),
+            };
+
+            Some(BuildAnomaly {
+                stat: anomaly.stat_diff.clone(),
+                slots: anomaly.slot_diffs.clone(),
+                skills: anomaly
+                    .skill_diffs
+                    .iter()
+                    .map(|(id, skill)| (id.clone(), skill.level))
+                    .collect(),
+            })
+        } else {
+            None
+        };
+
+        armors.push(Some(BuildArmor {
+            id: armor.original_id().clone(),
+            anomaly,
+        }));
+    }
+
+    let talisman = match full_equipments
+        .equipments
+        .iter()
+        .find(|equipment| equipment.part() == &ArmorPart::Talisman)
+    {
+        Some(equipment) => {
+            let talisman = equipment.as_talisman().base();
+
+            BuildTalisman {
+                skills: talisman
+                    .skills
+                    .iter()
+                    .map(|skill| (skill.id.clone(), skill.level))
+                    .collect(),
+                slot_sizes: talisman.slot_sizes.clone(),
+            }
+        }
+        None => BuildTalisman {
+            skills: Vec::new(),
+            slot_sizes: Vec::new(),
+        },
+    };
+
+    let decos = placement
+        .iter()
+        .filter_map(|slot| {
+            slot.deco_id.as_ref().map(|deco_id| BuildDeco {
+                part: slot.part.clone(),
+                slot_index: slot.slot_index,
+                id: deco_id.clone(),
+            })
+        })
+        .collect();
+
+    let skills = get_build_skills(dm, &full_equipments.all_skills, placement)?;
+
+    let build_code = BuildCode {
+        weapon_slots: full_equipments.weapon_slots.clone(),
+        armors,
+        talisman,
+        decos,
+        skills: skills.into_iter().collect(),
+    };
+
+    let json = serde_json::to_vec(&build_code)
+        .map_err(|err| format!("Failed to serialize build: {}", err))?;
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+
+    let compressed = encoder
+        .write_all(&json)
+        .and_then(|_| encoder.finish())
+        .map_err(|err| format!("Failed to compress build: {}", err))?;
+
+    Ok(format!(
+        "{}.{}",
+        BUILD_CODE_VERSION,
+        base64::encode_config(compressed, base64::URL_SAFE_NO_PAD)
+    ))
+}
+
+/// Rebuilds a build from its code, checking it against the data and its skills against the
+/// ones it was shared with
+pub fn decode_build(dm: &DataManager, code: &str) -> Result<DecodedBuild, String> {
+    let (version, payload) = match code.trim().split_once('.') {
+        Some(parts) => parts,
+        None => return Err("Invalid build code: no version".to_string()),
+    };
+
+    if version.parse::<u32>() != Ok(BUILD_CODE_VERSION) {
+        return Err(format!("Unsupported build code version: {}", version));
+    }
+
+    let compressed = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)
+        .map_err(|err| format!("Invalid build code: {}", err))?;
+
+    let mut json = Vec::new();
+
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_BUILD_CODE_LEN)
+        .read_to_end(&mut json)
+        .map_err(|err| format!("Invalid build code: {}", err))?;
+
+    let build_code = serde_json::from_slice::<BuildCode>(&json)
+        .map_err(|err| format!("Invalid build code: {}", err))?;
+
+    let decoded = rebuild(dm, &build_code)?;
+
+    let expected_skills = build_code
+        .skills
+        .into_iter()
+        .collect::<HashMap<String, i32>>();
+
+    if decoded.skills != expected_skills {
+        return Err(format!(
+            "Skills of the build differ from the shared ones, data may have changed: {:?} instead of {:?}",
+            decoded.skills, expected_skills
+        ));
+    }
+
+    Ok(decoded)
+}
+
+fn rebuild(dm: &DataManager, build_code: &BuildCode) -> Result<DecodedBuild, String> {
+    let all_parts = ArmorPart::get_all();
+
+    if build_code.armors.len() != all_parts.len() {
+        return Err(format!(
+            "Build has {} armors instead of {}",
+            build_code.armors.len(),
+            all_parts.len()
+        ));
+    }
+
+    for slot_size in &build_code.weapon_slots {
+        check_slot_size(*slot_size)?;
+    }
+
+    let mut armor_ids = Vec::new();
+    let mut anomalies = HashMap::new();
+
+    for (part, armor) in all_parts.iter().zip(&build_code.armors) {
+        let armor = match armor {
+            Some(armor) => armor,
+            None => {
+                armor_ids.push(dm.empty_armors[part].id().clone());
+                continue;
+            }
+        };
+
+        let base = match dm.armors.get(&armor.id) {
+            Some(base) => base,
+            None => return Err(format!("Unknown armor: {}", armor.id)),
+        };
+
+        if &base.part != part {
+            return Err(format!(
+                "Armor {} is not a {} armor",
+                armor.id,
+                part.as_str()
+            ));
+        }
+
+        armor_ids.push(armor.id.clone());
+
+        if let Some(anomaly) = &armor.anomaly {
+            for slot_size in &anomaly.slots {
+                check_slot_size(*slot_size)?;
+            }
+
+            let mut skill_diffs = HashMap::new();
+
+            for (skill_id, level) in &anomaly.skills {
+                check_skill(dm, skill_id, None)?;
+                skill_diffs.insert(skill_id.clone(), ArmorSkill { level: *level });
+            }
+
+            let mut anomaly_armor = AnomalyArmor::new(
+                base.clone(),
+                anomaly.stat.clone(),
+                anomaly.slots.clone(),
+                skill_diffs,
+            );
+
+            anomaly_armor.set_id(format!("{}_shared_{}", ANOMALY_ARMOR_PREFIX, base.id()));
+
+            anomalies.insert(part.clone(), anomaly_armor);
+        }
+    }
+
+    let mut talisman_skills = Vec::new();
+
+    for (skill_id, level) in &build_code.talisman.skills {
+        check_skill(dm, skill_id, Some(*level))?;
+
+        talisman_skills.push(TalismanSkill {
+            id: skill_id.clone(),
+            level: *level,
+        });
+    }
+
+    for slot_size in &build_code.talisman.slot_sizes {
+        check_slot_size(*slot_size)?;
+    }
+
+    let mut decoded = DecodedBuild {
+        weapon_slots: build_code.weapon_slots.clone(),
+        armor_ids,
+        anomalies,
+        talisman: Talisman::new(
+            SHARED_TALISMAN_ID.to_string(),
+            talisman_skills,
+            build_code.talisman.slot_sizes.clone(),
+        ),
+        placement: Vec::new(),
+        skills: HashMap::new(),
+    };
+
+    let (placement, skills) = place_decos(dm, &decoded, &build_code.decos)?;
+
+    decoded.placement = placement;
+    decoded.skills = skills;
+
+    Ok(decoded)
+}
+
+/// Puts decorations of the code into the slots of the rebuilt equipments
+fn place_decos(
+    dm: &DataManager,
+    build: &DecodedBuild,
+    decos: &Vec<BuildDeco>,
+) -> Result<(Vec<ResultDecorationSlot>, HashMap<String, i32>), String> {
+    let full_equipments = build.full_equipments(dm);
+
+    let mut placement = get_all_slots(&build.weapon_slots, &full_equipments.equipments);
+    let mut filled = HashSet::new();
+
+    for deco in decos {
+        let deco_info = match dm.decos.get(&deco.id) {
+            Some(deco_info) => deco_info,
+            None => return Err(format!("Unknown decoration: {}", deco.id)),
+        };
+
+        let slot = placement
+            .iter_mut()
+            .find(|slot| slot.part == deco.part && slot.slot_index == deco.slot_index);
+
+        let slot = match slot {
+            Some(slot) => slot,
+            None => {
+                return Err(format!(
+                    "No slot {} on {} for decoration {}",
+                    deco.slot_index, deco.part, deco.id
+                ))
+            }
+        };
+
+        if slot.slot_size < deco_info.slot_size {
+            return Err(format!(
+                "Decoration {} (size {}) does not fit {} slot {} (size {})",
+                deco.id, deco_info.slot_size, slot.part, slot.slot_index, slot.slot_size
+            ));
+        }
+
+        if !filled.insert((&deco.part, deco.slot_index)) {
+            return Err(format!(
+                "Slot {} on {} has more than one decoration",
+                deco.slot_index, deco.part
+            ));
+        }
+
+        slot.deco_id = Some(deco.id.clone());
+    }
+
+    let skills = get_build_skills(dm, &full_equipments.all_skills, &placement)?;
+
+    Ok((placement, skills))
+}
+
+/// Skills of the equipments plus the ones of the placed decorations
+fn get_build_skills(
+    dm: &DataManager,
+    equipment_skills: &HashMap<String, i32>,
+    placement: &Vec<ResultDecorationSlot>,
+) -> Result<HashMap<String, i32>, String> {
+    let mut skills = equipment_skills.clone();
+
+    for slot in placement {
+        let deco_id = match &slot.deco_id {
+            Some(deco_id) => deco_id,
+            None => continue,
+        };
+
+        let deco = match dm.decos.get(deco_id) {
+            Some(deco) => deco,
+            None => return Err(format!("Unknown decoration: {}", deco_id)),
+        };
+
+        *skills.entry(deco.skill_id.clone()).or_insert(0) += deco.skill_level;
+    }
+
+    Ok(skills)
+}
+
+/// Skill exists and `level`, if any, is within its levels
+fn check_skill(dm: &DataManager, skill_id: &String, level: Option<i32>) -> Result<(), String> {
+    let skill = match dm.skills.get(skill_id) {
+        Some(skill) => skill,
+        None => return Err(format!("Unknown skill: {}", skill_id)),
+    };
+
+    if let Some(level) = level {
+        if level < 1 || skill.max_level < level {
+            return Err(format!(
+                "Skill {} level {} is out of 1 to {}",
+                skill_id, level, skill.max_level
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_slot_size(slot_size: i32) -> Result<(), String> {
+    if slot_size < 0 || (MAX_SLOT_LEVEL as i32) < slot_size {
+        return Err(format!("Invalid slot size: {}", slot_size));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{decode_build, encode_build};
+    use crate::{
+        data::armor::{AnomalyArmor, ArmorPart, ArmorSkill, ArmorStat, Talisman, TalismanSkill},
+        deco_placement::ResultDecorationSlot,
+        full_equipments::FullEquipments,
+        search::search,
+        test::{create_pinned_request, get_data_manager},
+    };
+
+    #[test]
+    fn build_code_embeds_anomalies_and_talismans() {
+        let mut dm = get_data_manager().clone();
+
+        let mut skill_diffs = HashMap::new();
+        skill_diffs.insert("attack_boost".to_string(), ArmorSkill { level: 2 });
+
+        dm.set_anomalies(vec![AnomalyArmor::new(
+            dm.armors["silver_solhelm"].clone(),
+            ArmorStat {
+                defense: 10,
+                ..Default::default()
+            },
+            vec![1, 0, 0],
+            skill_diffs,
+        )]);
+
+        dm.set_talismans(vec![Talisman::new(
+            "talisman_0".to_string(),
+            vec![TalismanSkill {
+                id: "critical_eye".to_string(),
+                level: 2,
+            }],
+            vec![2, 1, 0],
+        )]);
+
+        let mut request = create_pinned_request(&[
+            ("critical_boost", 3),
+            ("attack_boost", 4),
+            ("critical_eye", 4),
+            ("weakness_exploit", 2),
+        ]);
+
+        request
+            .constraints
+            .pinned_armors
+            .insert(ArmorPart::Helm, "__anomaly_0_silver_solhelm".to_string());
+
+        request.constraints.pinned_talisman = Some("talisman_0".to_string());
+        request.limits.max_results = 3;
+
+        let result = search(&dm, &request).unwrap();
+
+        assert_ne!(result.full_equipments.len(), 0);
+
+        let result_equip = &result.full_equipments[0];
+        let placement = &result_equip.deco_combs[0].placement;
+        let equipments =
+            FullEquipments::from_result(&dm, request.weapon_slots.clone(), result_equip).unwrap();
+
+        let code = encode_build(&dm, &equipments, placement).unwrap();
+
+        assert!(code.starts_with("1."));
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'));
+
+        // Decoded by someone without the anomaly and talisman files
+        let other_dm = get_data_manager();
+
+        let decoded = decode_build(other_dm, &code).unwrap();
+        let decoded_equipments = decoded.full_equipments(other_dm);
+
+        assert_eq!(decoded_equipments.all_skills, equipments.all_skills);
+        assert_eq!(decoded_equipments.avail_slots, equipments.avail_slots);
+        assert_eq!(decoded.anomalies[&ArmorPart::Helm].stat_diff.defense, 10);
+        assert_eq!(decoded.talisman.slot_sizes, vec![2, 1, 0]);
+
+        let get_filled_slots = |placement: &Vec<ResultDecorationSlot>| {
+            let mut filled_slots = placement
+                .iter()
+                .filter_map(|slot| {
+                    let deco_id = slot.deco_id.clone()?;
+                    Some((slot.part.clone(), slot.slot_index, deco_id))
+                })
+                .collect::<Vec<_>>();
+
+            filled_slots.sort();
+            filled_slots
+        };
+
+        assert_ne!(get_filled_slots(placement).len(), 0);
+        assert_eq!(
+            get_filled_slots(&decoded.placement),
+            get_filled_slots(placement)
+        );
+
+        for (skill_id, level) in &request.selected_skills {
+            assert!(level <= &decoded.skills[skill_id]);
+        }
+
+        assert!(decode_build(other_dm, &code.replacen("1.", "2.", 1)).is_err());
+        assert!(decode_build(other_dm, "1.not_a_build").is_err());
+    }
+}
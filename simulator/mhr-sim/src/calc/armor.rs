@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use crate::data::{
-    armor::{ArmorPart, BaseArmor, SexType},
+    armor::{ArmorPart, ArmorStat, BaseArmor, SexType},
     skill::MAX_SLOT_LEVEL,
 };
 
@@ -57,20 +57,33 @@ impl<'a> CalcArmor<'a> {
         }
     }
 
+    pub fn base(&self) -> &'a BaseArmor {
+        self.base
+    }
+
+    pub fn original(&self) -> &'a BaseArmor {
+        self.original
+    }
+
     pub fn is_anomaly(&self) -> bool {
-        BaseArmor::is_anomaly_armor(&self.base.id())
+        BaseArmor::is_anomaly_armor(self.base.id())
     }
 
     pub fn original_id(&self) -> &String {
-        &self.original.id()
+        self.original.id()
+    }
+
+    /// Stat with anomaly crafting applied
+    pub fn stat(&self) -> &ArmorStat {
+        &self.base.stat
     }
 
     pub fn sex_type(&self) -> &SexType {
-        &self.base.sex_type
+        &self.sex_type
     }
 
     pub fn rarity(&self) -> i32 {
-        self.base.rarity
+        self.rarity
     }
 
     pub fn name(&self, lang: &str) -> String {
@@ -117,11 +130,7 @@ impl<'a> CalcArmor<'a> {
     }
 
     pub fn convert_from_base_slots(base_slots: &Vec<i32>) -> Vec<i32> {
-        let mut ret = Vec::new();
-
-        for _ in 0..MAX_SLOT_LEVEL {
-            ret.push(0);
-        }
+        let mut ret = vec![0; MAX_SLOT_LEVEL];
 
         for slot_size in base_slots {
             if *slot_size == 0 {
@@ -137,7 +146,7 @@ impl<'a> CalcArmor<'a> {
 
 impl<'a> CalcEquipment<'a> for CalcArmor<'a> {
     fn id(&self) -> &String {
-        &self.base.id()
+        self.base.id()
     }
 
     fn skills(&self) -> &HashMap<String, i32> {
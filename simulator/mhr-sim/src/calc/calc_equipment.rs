@@ -4,7 +4,7 @@ use crate::data::{armor::ArmorPart, deco::Decoration, skill::MAX_SLOT_LEVEL};
 
 use super::{armor::CalcArmor, deco::CalcDeco, talisman::CalcTalisman};
 
-pub trait CalcEquipment<'a> {
+pub trait CalcEquipment<'a>: Send + Sync {
     fn id(&self) -> &String;
     fn skills(&self) -> &HashMap<String, i32>;
     fn mut_skills(&mut self) -> &mut HashMap<String, i32>;
@@ -71,9 +71,8 @@ pub trait CalcEquipment<'a> {
                     point += level.min(req_level) * max_slot_size;
                 }
                 None => {
-                    match no_deco_skills.get(id) {
-                        Some(&req_level) => point += level.min(req_level) * 1000,
-                        None => {}
+                    if let Some(&req_level) = no_deco_skills.get(id) {
+                        point += level.min(req_level) * 1000
                     };
                 }
             };
@@ -107,7 +106,7 @@ pub trait CalcEquipment<'a> {
             diffs.insert(id, taken);
         }
 
-        return diffs;
+        diffs
     }
 }
 
@@ -121,8 +120,4 @@ impl<'a> PartialEq for dyn CalcEquipment<'a> {
     fn eq(&self, other: &Self) -> bool {
         self.slots() == other.slots() && self.skills() == other.skills()
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.eq(other) == false
-    }
 }
@@ -1,9 +1,6 @@
 use std::collections::HashMap;
 
-use crate::data::{
-    armor::{ArmorPart, Talisman},
-    skill::MAX_SLOT_LEVEL,
-};
+use crate::data::armor::{ArmorPart, Talisman};
 
 use super::{armor::CalcArmor, calc_equipment::CalcEquipment};
 
@@ -33,9 +30,15 @@ impl<'a> CalcTalisman<'a> {
     }
 }
 
+impl<'a> CalcTalisman<'a> {
+    pub fn base(&self) -> &'a Talisman {
+        self.tali
+    }
+}
+
 impl<'a> CalcEquipment<'a> for CalcTalisman<'a> {
     fn id(&self) -> &String {
-        &self.tali.id()
+        self.tali.id()
     }
 
     fn skills(&self) -> &HashMap<String, i32> {
@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::armor_inventory::{ArmorAvailability, ArmorInventory};
+use crate::data::armor::{ArmorPart, ArmorRank, ArmorStat, BaseArmor, StatType, Talisman};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SearchConstraints {
+    /// Armor id (base or anomaly) each part is locked to
+    #[serde(rename = "pinnedArmors", default)]
+    pub pinned_armors: HashMap<ArmorPart, String>,
+
+    #[serde(rename = "pinnedTalisman", default)]
+    pub pinned_talisman: Option<String>,
+
+    /// Excluding a base armor id also excludes the anomaly armors crafted from it
+    #[serde(rename = "excludedArmors", default)]
+    pub excluded_armors: HashSet<String>,
+
+    #[serde(rename = "excludedSeries", default)]
+    pub excluded_series: HashSet<String>,
+
+    #[serde(rename = "excludedTalismans", default)]
+    pub excluded_talismans: HashSet<String>,
+
+    /// Lower bounds of the total stat of armors, e.g. minimum defense
+    #[serde(rename = "minStats", default)]
+    pub min_stats: HashMap<StatType, i32>,
+
+    #[serde(rename = "maxStats", default)]
+    pub max_stats: HashMap<StatType, i32>,
+
+    /// Ranks of armors to search, any rank if empty
+    #[serde(default)]
+    pub ranks: HashSet<ArmorRank>,
+
+    /// Rarity range of armors to search, 0-based as in data
+    #[serde(rename = "minRarity", default)]
+    pub min_rarity: Option<i32>,
+
+    #[serde(rename = "maxRarity", default)]
+    pub max_rarity: Option<i32>,
+
+    /// Restricts armors to the owned (and craftable) ones of the armor inventory
+    #[serde(rename = "armorAvailability", default)]
+    pub armor_availability: ArmorAvailability,
+}
+
+impl SearchConstraints {
+    pub fn is_pinned(&self, part: &ArmorPart) -> bool {
+        if part == &ArmorPart::Talisman {
+            return self.pinned_talisman.is_some();
+        }
+
+        self.pinned_armors.contains_key(part)
+    }
+
+    /// `armor` is the real armor (anomaly applied) and `original` is its base armor
+    pub fn allows_armor(
+        &self,
+        armor: &BaseArmor,
+        original: &BaseArmor,
+        inventory: &ArmorInventory,
+    ) -> bool {
+        if let Some(pinned_id) = self.pinned_armors.get(&armor.part) {
+            return pinned_id == armor.id();
+        }
+
+        if self.excluded_armors.contains(armor.id()) || self.excluded_armors.contains(original.id())
+        {
+            return false;
+        }
+
+        if !inventory.is_available(armor, self.armor_availability) {
+            return false;
+        }
+
+        if !self.allows_rarity(original.rarity) {
+            return false;
+        }
+
+        !self.excluded_series.contains(&original.series)
+    }
+
+    /// Anomaly armors keep the rarity of their base armor
+    pub fn allows_rarity(&self, rarity: i32) -> bool {
+        if !self.ranks.is_empty() && !self.ranks.contains(&ArmorRank::from_rarity(rarity)) {
+            return false;
+        }
+
+        if let Some(min_rarity) = self.min_rarity {
+            if rarity < min_rarity {
+                return false;
+            }
+        }
+
+        if let Some(max_rarity) = self.max_rarity {
+            if max_rarity < rarity {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn allows_talisman(&self, tali: &Talisman) -> bool {
+        if let Some(pinned_id) = &self.pinned_talisman {
+            return pinned_id == tali.id();
+        }
+
+        !self.excluded_talismans.contains(tali.id())
+    }
+
+    pub fn has_stat_limits(&self) -> bool {
+        !self.min_stats.is_empty() || !self.max_stats.is_empty()
+    }
+
+    pub fn allows_stat(&self, total_stat: &ArmorStat) -> bool {
+        for (stat_type, min_value) in &self.min_stats {
+            if total_stat.get(stat_type) < *min_value {
+                return false;
+            }
+        }
+
+        for (stat_type, max_value) in &self.max_stats {
+            if *max_value < total_stat.get(stat_type) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchConstraints;
+    use crate::{
+        data::armor::{ArmorRank, ArmorStat, StatType},
+        test::get_data_manager,
+    };
+
+    #[test]
+    fn constraints_exclude_series() {
+        let dm = get_data_manager();
+
+        let helm = &dm.armors["goss_harag_helm"];
+        let greaves = &dm.armors["goss_harag_greaves"];
+        let greaves_s = &dm.armors["goss_harag_greaves_s"];
+
+        assert_eq!(greaves.series, helm.id().clone());
+        assert_eq!(greaves_s.series, "goss_harag_helm_s");
+
+        let mut constraints = SearchConstraints::default();
+        constraints.excluded_series.insert(helm.id().clone());
+
+        assert!(!constraints.allows_armor(greaves, greaves, &dm.armor_inventory));
+        assert!(constraints.allows_armor(greaves_s, greaves_s, &dm.armor_inventory));
+    }
+
+    #[test]
+    fn constraints_stat_limits() {
+        let dm = get_data_manager();
+
+        let mut total_stat = ArmorStat::default();
+
+        for id in ["silver_solhelm", "silver_solmail", "silver_solbraces"] {
+            total_stat.add(&dm.armors[id].stat);
+        }
+
+        let fire_res = total_stat.get(&StatType::FireRes);
+
+        let mut constraints = SearchConstraints::default();
+        constraints.min_stats.insert(StatType::FireRes, fire_res);
+
+        assert!(constraints.allows_stat(&total_stat));
+
+        constraints
+            .max_stats
+            .insert(StatType::FireRes, fire_res - 1);
+
+        assert!(!constraints.allows_stat(&total_stat));
+    }
+
+    #[test]
+    fn constraints_rank_and_rarity() {
+        let dm = get_data_manager();
+
+        let scarf = &dm.armors["kamura_head_scarf"];
+        let solhelm = &dm.armors["silver_solhelm"];
+
+        assert_eq!(ArmorRank::from_rarity(scarf.rarity), ArmorRank::Low);
+        assert_eq!(ArmorRank::from_rarity(solhelm.rarity), ArmorRank::Master);
+
+        let mut constraints = SearchConstraints::default();
+        constraints.ranks.insert(ArmorRank::Low);
+        constraints.ranks.insert(ArmorRank::High);
+
+        let inventory = &dm.armor_inventory;
+
+        assert!(constraints.allows_armor(scarf, scarf, inventory));
+        assert!(!constraints.allows_armor(solhelm, solhelm, inventory));
+
+        constraints.ranks.clear();
+        constraints.min_rarity = Some(1);
+
+        assert!(!constraints.allows_armor(scarf, scarf, inventory));
+        assert!(constraints.allows_armor(solhelm, solhelm, inventory));
+
+        constraints.max_rarity = Some(solhelm.rarity - 1);
+
+        assert!(!constraints.allows_armor(solhelm, solhelm, inventory));
+    }
+}
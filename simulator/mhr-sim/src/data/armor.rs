@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
 use lazy_static::lazy_static;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use tauri::regex::Regex;
 
 pub static EMPTY_ARMOR_PREFIX: &str = "__empty";
 pub static SLOT_ARMOR_PREFIX: &str = "__slot";
@@ -42,9 +42,32 @@ impl ArmorPart {
     }
 }
 
+/// Rank of an armor by its rarity, which is 0-based in data (in game rarity minus one)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum ArmorRank {
+    #[serde(rename = "low")]
+    Low,
+    #[serde(rename = "high")]
+    High,
+    #[serde(rename = "master")]
+    Master,
+}
+
+impl ArmorRank {
+    pub fn from_rarity(rarity: i32) -> Self {
+        if 7 <= rarity {
+            ArmorRank::Master
+        } else if 3 <= rarity {
+            ArmorRank::High
+        } else {
+            ArmorRank::Low
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum SexType {
-    #[serde(rename = "all")]
+    #[serde(rename = "all", alias = "either")]
     All,
     #[serde(rename = "male")]
     Male,
@@ -52,7 +75,7 @@ pub enum SexType {
     Female,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ArmorStat {
     pub defense: i32,
     #[serde(rename = "fireRes")]
@@ -67,6 +90,68 @@ pub struct ArmorStat {
     pub dragon_res: i32,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub enum StatType {
+    #[serde(rename = "defense")]
+    Defense,
+    #[serde(rename = "fireRes")]
+    FireRes,
+    #[serde(rename = "waterRes")]
+    WaterRes,
+    #[serde(rename = "iceRes")]
+    IceRes,
+    #[serde(rename = "elecRes")]
+    ElecRes,
+    #[serde(rename = "dragonRes")]
+    DragonRes,
+}
+
+impl StatType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatType::Defense => "defense",
+            StatType::FireRes => "fireRes",
+            StatType::WaterRes => "waterRes",
+            StatType::IceRes => "iceRes",
+            StatType::ElecRes => "elecRes",
+            StatType::DragonRes => "dragonRes",
+        }
+    }
+
+    pub fn get_all() -> Vec<Self> {
+        vec![
+            Self::Defense,
+            Self::FireRes,
+            Self::WaterRes,
+            Self::IceRes,
+            Self::ElecRes,
+            Self::DragonRes,
+        ]
+    }
+}
+
+impl ArmorStat {
+    pub fn get(&self, stat_type: &StatType) -> i32 {
+        match stat_type {
+            StatType::Defense => self.defense,
+            StatType::FireRes => self.fire_res,
+            StatType::WaterRes => self.water_res,
+            StatType::IceRes => self.ice_res,
+            StatType::ElecRes => self.elec_res,
+            StatType::DragonRes => self.dragon_res,
+        }
+    }
+
+    pub fn add(&mut self, other: &ArmorStat) {
+        self.defense += other.defense;
+        self.fire_res += other.fire_res;
+        self.water_res += other.water_res;
+        self.ice_res += other.ice_res;
+        self.elec_res += other.elec_res;
+        self.dragon_res += other.dragon_res;
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ArmorSkill {
     pub level: i32,
@@ -85,6 +170,10 @@ pub struct BaseArmor {
     pub stat: ArmorStat,
     pub skills: HashMap<String, ArmorSkill>,
     pub slots: Vec<i32>,
+
+    /// Id of the first armor of the series, derived by `assign_series` if missing in data
+    #[serde(default)]
+    pub series: String,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -102,7 +191,7 @@ pub struct AnomalyArmor {
     pub skill_diffs: HashMap<String, ArmorSkill>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TalismanSkill {
     pub id: String,
     pub level: i32,
@@ -115,9 +204,16 @@ pub struct Talisman {
     pub slot_sizes: Vec<i32>,
 }
 
+impl SexType {
+    /// Hunter with `All` sex type (either) can wear armors of every sex type
+    pub fn is_wearable_by(&self, hunter_sex_type: &SexType) -> bool {
+        self == &SexType::All || hunter_sex_type == &SexType::All || self == hunter_sex_type
+    }
+}
+
 impl ArmorPart {
     pub fn get_all() -> Vec<Self> {
-        return vec![Self::Helm, Self::Torso, Self::Arm, Self::Waist, Self::Feet];
+        vec![Self::Helm, Self::Torso, Self::Arm, Self::Waist, Self::Feet]
     }
 }
 
@@ -131,6 +227,7 @@ impl BaseArmor {
             sex_type: SexType::All,
             skills: HashMap::new(),
             slots: vec![0, 0, 0],
+            series: String::new(),
             stat: ArmorStat {
                 defense: 0,
                 fire_res: 0,
@@ -146,14 +243,14 @@ impl BaseArmor {
         &self.id
     }
 
-    pub fn get_slot_armor_id(slots: &Vec<i32>) -> String {
+    pub fn get_slot_armor_id(slots: &[i32]) -> String {
         format!(
             "{}_{}-{}-{}",
             SLOT_ARMOR_PREFIX, slots[0], slots[1], slots[2]
         )
     }
 
-    pub fn parse_slot_armor_id(slot_armor_id: &String) -> Vec<i32> {
+    pub fn parse_slot_armor_id(slot_armor_id: &str) -> Vec<i32> {
         let mut ret = Vec::new();
 
         for cap in SLOT_ARMOR_REGEX.captures_iter(slot_armor_id) {
@@ -165,14 +262,52 @@ impl BaseArmor {
         ret
     }
 
-    pub fn is_slot_armor(armor_id: &String) -> bool {
+    // armor.json lists each series as a contiguous helm to feet run with the same rarity and sex type
+    pub fn assign_series(armors: &mut [BaseArmor]) {
+        let all_parts = ArmorPart::get_all();
+
+        let mut series = String::new();
+        let mut prev_armor: Option<(usize, i32, SexType)> = None;
+
+        for armor in armors.iter_mut() {
+            let part_index = all_parts
+                .iter()
+                .position(|part| part == &armor.part)
+                .unwrap_or(0);
+
+            let is_same_series = match &prev_armor {
+                Some((prev_index, rarity, sex_type)) => {
+                    *prev_index < part_index
+                        && *rarity == armor.rarity
+                        && sex_type == &armor.sex_type
+                }
+                None => false,
+            };
+
+            if !is_same_series {
+                series = armor.id.clone();
+            }
+
+            prev_armor = Some((part_index, armor.rarity, armor.sex_type.clone()));
+
+            if armor.series.is_empty() {
+                armor.series = series.clone();
+            }
+        }
+    }
+
+    pub fn is_slot_armor(armor_id: &str) -> bool {
         armor_id.starts_with(SLOT_ARMOR_PREFIX)
     }
 
-    pub fn is_anomaly_armor(armor_id: &String) -> bool {
+    pub fn is_anomaly_armor(armor_id: &str) -> bool {
         armor_id.starts_with(ANOMALY_ARMOR_PREFIX)
     }
 
+    pub fn is_empty_armor(armor_id: &str) -> bool {
+        armor_id.starts_with(EMPTY_ARMOR_PREFIX)
+    }
+
     pub fn get_slot_armor(part: ArmorPart, slot_armor_id: String) -> BaseArmor {
         Self {
             id: slot_armor_id.clone(),
@@ -182,6 +317,7 @@ impl BaseArmor {
             sex_type: SexType::All,
             skills: HashMap::new(),
             slots: BaseArmor::parse_slot_armor_id(&slot_armor_id),
+            series: String::new(),
             stat: ArmorStat {
                 defense: 0,
                 fire_res: 0,
@@ -213,17 +349,12 @@ impl AnomalyArmor {
         for (id, skill_info) in &skill_diffs {
             let diff_level = skill_info.level;
 
-            let new_value;
-            let existing_skill = affected.skills.get(id);
-
-            if existing_skill.is_some() {
-                let old_value = existing_skill.unwrap();
-                new_value = ArmorSkill {
+            let new_value = match affected.skills.get(id) {
+                Some(old_value) => ArmorSkill {
                     level: old_value.level + diff_level,
-                };
-            } else {
-                new_value = ArmorSkill { level: diff_level }
-            }
+                },
+                None => ArmorSkill { level: diff_level },
+            };
 
             affected.skills.insert(id.clone(), new_value);
         }
@@ -244,10 +375,18 @@ impl AnomalyArmor {
             skill_diffs,
         }
     }
+
+    /// Anomaly ids are given by who holds the armors, e.g. by index of the loaded file
+    pub fn set_id(&mut self, id: String) {
+        self.affected.id = id;
+    }
 }
 
 impl Talisman {
-    pub fn new(id: String, skills: Vec<TalismanSkill>, slot_sizes: Vec<i32>) -> Self {
+    /// Slot sizes are sorted from the largest, as shown in game
+    pub fn new(id: String, skills: Vec<TalismanSkill>, mut slot_sizes: Vec<i32>) -> Self {
+        slot_sizes.sort_by(|size1, size2| size2.cmp(size1));
+
         Self {
             id,
             skills,
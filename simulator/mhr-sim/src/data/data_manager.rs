@@ -0,0 +1,768 @@
+use std::collections::HashMap;
+
+use crate::armor_inventory::{ArmorInventory, ArmorOwnership, OwnedArmor};
+
+use super::armor::{
+    AnomalyArmor, ArmorPart, BaseArmor, Talisman, TalismanSkill, ANOMALY_ARMOR_PREFIX,
+};
+use super::data_validation::ARMOR_SLOT_COUNT;
+use super::deco::Decoration;
+use super::deco_combination::DecorationCombinations;
+use super::name_dict::{NameCollision, NameDict};
+use super::skill::{Skill, MAX_SLOT_LEVEL};
+
+/// Slot size and required level of skills with a single decoration
+pub type SingleDecoSkills = HashMap<String, (i32, i32)>;
+
+#[derive(Clone)]
+pub struct DataManager {
+    pub armors: HashMap<String, BaseArmor>,
+    pub skills: HashMap<String, Skill>,
+    pub decos: HashMap<String, Decoration>,
+
+    pub decos_by_skill: HashMap<String, Vec<Decoration>>,
+    pub deco_combinations: DecorationCombinations,
+
+    pub slot_only_armors: HashMap<ArmorPart, HashMap<String, BaseArmor>>,
+    pub armors_by_slot: HashMap<ArmorPart, HashMap<String, Vec<BaseArmor>>>,
+    pub empty_armors: HashMap<ArmorPart, BaseArmor>,
+    pub anomaly_armors: HashMap<String, AnomalyArmor>,
+
+    pub bases_by_part: HashMap<ArmorPart, Vec<BaseArmor>>,
+    pub anomalies_by_part: HashMap<ArmorPart, Vec<BaseArmor>>,
+
+    pub armor_inventory: ArmorInventory,
+
+    pub talismans: Vec<Talisman>,
+    pub slot_only_talismans: HashMap<String, Talisman>,
+    pub talismans_by_slot: HashMap<String, Vec<Talisman>>,
+    pub empty_talisman: Talisman,
+    /// Index of the next talisman id, never reused after deletes or imports
+    next_talisman_index: usize,
+
+    pub armor_name_dict: NameDict,
+    pub skill_name_dict: NameDict,
+    pub deco_name_dict: NameDict,
+}
+
+impl DataManager {
+    pub fn new(
+        armors: HashMap<String, BaseArmor>,
+        skills: HashMap<String, Skill>,
+        decos: HashMap<String, Decoration>,
+    ) -> Result<Self, String> {
+        let armor_name_dict =
+            NameDict::new("armor", armors.iter().map(|(id, armor)| (id, &armor.names)));
+        let skill_name_dict =
+            NameDict::new("skill", skills.iter().map(|(id, skill)| (id, &skill.names)));
+        let deco_name_dict = NameDict::new(
+            "decoration",
+            decos.iter().map(|(id, deco)| (id, &deco.names)),
+        );
+
+        let mut decos_by_skill = HashMap::<String, Vec<Decoration>>::new();
+
+        for pair in &decos {
+            let deco = pair.1;
+            let skill_id = &deco.skill_id;
+
+            let existing = decos_by_skill.get_mut(skill_id);
+
+            match existing {
+                Some(vec) => vec.push(deco.clone()),
+                None => {
+                    decos_by_skill.insert(skill_id.clone(), vec![deco.clone()]);
+                }
+            }
+        }
+
+        for pair in decos_by_skill.iter_mut() {
+            pair.1.sort_by_key(|a| a.skill_level);
+        }
+
+        let deco_combinations = DecorationCombinations::new(&decos_by_skill, &skills)?;
+
+        let mut slot_only_armors = HashMap::<ArmorPart, HashMap<String, BaseArmor>>::new();
+        let mut armors_by_slot = HashMap::<ArmorPart, HashMap<String, Vec<BaseArmor>>>::new();
+
+        for part in ArmorPart::get_all() {
+            slot_only_armors.insert(part.clone(), HashMap::new());
+            armors_by_slot.insert(part, HashMap::new());
+        }
+
+        for armor in armors.values() {
+            Self::insert_by_slot(&mut slot_only_armors, &mut armors_by_slot, armor);
+        }
+
+        // Keeps search results independent of hash map iteration order
+        for (_, part_slot_armors) in armors_by_slot.iter_mut() {
+            for (_, slot_armors) in part_slot_armors.iter_mut() {
+                slot_armors.sort_by(|armor1, armor2| armor1.id().cmp(armor2.id()));
+            }
+        }
+
+        let mut empty_armors = HashMap::<ArmorPart, BaseArmor>::new();
+
+        for part in ArmorPart::get_all() {
+            empty_armors.insert(part.clone(), BaseArmor::create_empty(part));
+        }
+
+        let mut bases_by_part = HashMap::<ArmorPart, Vec<BaseArmor>>::new();
+
+        bases_by_part.insert(ArmorPart::Helm, Vec::new());
+        bases_by_part.insert(ArmorPart::Torso, Vec::new());
+        bases_by_part.insert(ArmorPart::Arm, Vec::new());
+        bases_by_part.insert(ArmorPart::Waist, Vec::new());
+        bases_by_part.insert(ArmorPart::Feet, Vec::new());
+
+        let anomalies_by_part = bases_by_part.clone();
+
+        for armor in armors.values() {
+            let part = &armor.part;
+            bases_by_part.get_mut(part).unwrap().push(armor.clone());
+        }
+
+        for (_, part_armors) in bases_by_part.iter_mut() {
+            part_armors.sort_by(|armor1, armor2| armor1.id().cmp(armor2.id()));
+        }
+
+        let dm = DataManager {
+            armors,
+            skills,
+            decos,
+            decos_by_skill,
+            deco_combinations,
+            slot_only_armors,
+            armors_by_slot,
+            empty_armors,
+            armor_name_dict,
+            skill_name_dict,
+            deco_name_dict,
+            bases_by_part,
+            anomalies_by_part,
+            empty_talisman: Talisman::create_empty(),
+            next_talisman_index: 0,
+            anomaly_armors: Default::default(),
+            armor_inventory: Default::default(),
+            talismans: Default::default(),
+            slot_only_talismans: Default::default(),
+            talismans_by_slot: Default::default(),
+        };
+
+        Ok(dm)
+    }
+
+    /// Names shared by several armors, skills or decorations, which need a language to look up
+    pub fn get_name_collisions(&self) -> Vec<NameCollision> {
+        let mut collisions = self.armor_name_dict.get_collisions();
+        collisions.append(&mut self.skill_name_dict.get_collisions());
+        collisions.append(&mut self.deco_name_dict.get_collisions());
+
+        collisions
+    }
+
+    pub fn set_anomalies(&mut self, anomalies: Vec<AnomalyArmor>) {
+        let mut anomalies = anomalies;
+
+        for (index, armor) in anomalies.iter_mut().enumerate() {
+            armor.set_id(format!(
+                "{}_{}_{}",
+                ANOMALY_ARMOR_PREFIX,
+                index,
+                armor.original.id()
+            ));
+        }
+
+        self.anomaly_armors = anomalies
+            .iter_mut()
+            .map(|anomaly| (anomaly.affected.id.clone(), anomaly.clone()))
+            .collect::<HashMap<String, AnomalyArmor>>();
+
+        for part_armors in self.anomalies_by_part.iter_mut() {
+            part_armors.1.clear();
+        }
+
+        for anomaly in self.anomaly_armors.values() {
+            let part = &anomaly.original.part;
+
+            self.anomalies_by_part
+                .get_mut(part)
+                .unwrap()
+                .push(anomaly.affected.clone());
+        }
+
+        for (_, part_armors) in self.anomalies_by_part.iter_mut() {
+            part_armors.sort_by(|armor1, armor2| armor1.id().cmp(armor2.id()));
+        }
+
+        // Anomalies without requested skills are searched by their slots like base armors
+        for (_, part_slot_armors) in self.armors_by_slot.iter_mut() {
+            for (_, slot_armors) in part_slot_armors.iter_mut() {
+                slot_armors.retain(|armor| !BaseArmor::is_anomaly_armor(armor.id()));
+            }
+
+            part_slot_armors.retain(|_, slot_armors| !slot_armors.is_empty());
+        }
+
+        for (part, part_slot_only_armors) in self.slot_only_armors.iter_mut() {
+            let part_slot_armors = &self.armors_by_slot[part];
+
+            part_slot_only_armors.retain(|id, _| part_slot_armors.contains_key(id));
+        }
+
+        for anomaly in self.anomaly_armors.values() {
+            Self::insert_by_slot(
+                &mut self.slot_only_armors,
+                &mut self.armors_by_slot,
+                &anomaly.affected,
+            );
+        }
+
+        for (_, part_slot_armors) in self.armors_by_slot.iter_mut() {
+            for (_, slot_armors) in part_slot_armors.iter_mut() {
+                slot_armors.sort_by(|armor1, armor2| armor1.id().cmp(armor2.id()));
+            }
+        }
+    }
+
+    fn insert_by_slot(
+        slot_only_armors: &mut HashMap<ArmorPart, HashMap<String, BaseArmor>>,
+        armors_by_slot: &mut HashMap<ArmorPart, HashMap<String, Vec<BaseArmor>>>,
+        armor: &BaseArmor,
+    ) {
+        let slot_armor_id = BaseArmor::get_slot_armor_id(&armor.slots);
+
+        let part_slot_only_armors = slot_only_armors.get_mut(&armor.part).unwrap();
+        if !part_slot_only_armors.contains_key(&slot_armor_id) {
+            part_slot_only_armors.insert(
+                slot_armor_id.clone(),
+                BaseArmor::get_slot_armor(armor.part.clone(), slot_armor_id.clone()),
+            );
+        }
+
+        armors_by_slot
+            .get_mut(&armor.part)
+            .unwrap()
+            .entry(slot_armor_id)
+            .or_default()
+            .push(armor.clone());
+    }
+
+    pub fn set_armor_inventory(&mut self, owned_armors: Vec<OwnedArmor>) {
+        self.armor_inventory = ArmorInventory::new(&owned_armors);
+    }
+
+    /// `None` ownership removes the armor from the inventory
+    pub fn set_armor_ownership(
+        &mut self,
+        armor_id: &String,
+        ownership: Option<ArmorOwnership>,
+    ) -> Result<(), String> {
+        if !self.armors.contains_key(armor_id) {
+            return Err(format!("Unknown armor {}", armor_id));
+        }
+
+        self.armor_inventory.set_ownership(armor_id, ownership);
+
+        Ok(())
+    }
+
+    /// Replaces all talismans, e.g. on a csv import. They are numbered after every talisman
+    /// set or added before, so a re-import never gives an id to a different talisman.
+    pub fn set_talismans(&mut self, talismans: Vec<Talisman>) {
+        let mut numbered = Vec::new();
+
+        for tali in talismans {
+            numbered.push(Talisman::new(
+                self.next_talisman_id(),
+                tali.skills,
+                tali.slot_sizes,
+            ));
+        }
+
+        self.talismans = numbered;
+
+        self.slot_only_talismans.clear();
+        self.talismans_by_slot.clear();
+
+        for tali in &self.talismans {
+            Self::insert_talisman_by_slot(
+                &mut self.slot_only_talismans,
+                &mut self.talismans_by_slot,
+                tali,
+            );
+        }
+    }
+
+    /// Adds a talisman entered by hand, returns it with its new id
+    pub fn add_talisman(
+        &mut self,
+        skills: Vec<TalismanSkill>,
+        slot_sizes: Vec<i32>,
+    ) -> Result<Talisman, String> {
+        self.check_talisman(&skills, &slot_sizes)?;
+
+        let tali = Talisman::new(self.next_talisman_id(), skills, slot_sizes);
+
+        Self::insert_talisman_by_slot(
+            &mut self.slot_only_talismans,
+            &mut self.talismans_by_slot,
+            &tali,
+        );
+        self.talismans.push(tali.clone());
+
+        Ok(tali)
+    }
+
+    fn next_talisman_id(&mut self) -> String {
+        let id = format!("talisman_{}", self.next_talisman_index);
+        self.next_talisman_index += 1;
+
+        id
+    }
+
+    /// Replaces the skills and slots of a talisman, keeping its id
+    pub fn edit_talisman(
+        &mut self,
+        tali_id: &String,
+        skills: Vec<TalismanSkill>,
+        slot_sizes: Vec<i32>,
+    ) -> Result<Talisman, String> {
+        self.check_talisman(&skills, &slot_sizes)?;
+
+        let index = self.get_talisman_index(tali_id)?;
+
+        Self::remove_talisman_by_slot(
+            &mut self.slot_only_talismans,
+            &mut self.talismans_by_slot,
+            &self.talismans[index],
+        );
+
+        let tali = Talisman::new(tali_id.clone(), skills, slot_sizes);
+
+        Self::insert_talisman_by_slot(
+            &mut self.slot_only_talismans,
+            &mut self.talismans_by_slot,
+            &tali,
+        );
+        self.talismans[index] = tali.clone();
+
+        Ok(tali)
+    }
+
+    pub fn delete_talisman(&mut self, tali_id: &String) -> Result<(), String> {
+        let index = self.get_talisman_index(tali_id)?;
+
+        let tali = self.talismans.remove(index);
+
+        Self::remove_talisman_by_slot(
+            &mut self.slot_only_talismans,
+            &mut self.talismans_by_slot,
+            &tali,
+        );
+
+        Ok(())
+    }
+
+    fn get_talisman_index(&self, tali_id: &String) -> Result<usize, String> {
+        self.talismans
+            .iter()
+            .position(|tali| tali.id() == tali_id)
+            .ok_or(format!("Unknown talisman {}", tali_id))
+    }
+
+    fn check_talisman(&self, skills: &[TalismanSkill], slot_sizes: &[i32]) -> Result<(), String> {
+        for (index, tali_skill) in skills.iter().enumerate() {
+            let skill = match self.skills.get(&tali_skill.id) {
+                Some(skill) => skill,
+                None => return Err(format!("Unknown skill {}", tali_skill.id)),
+            };
+
+            if tali_skill.level < 1 || skill.max_level < tali_skill.level {
+                return Err(format!(
+                    "Skill {} level {} is out of 1 to {}",
+                    tali_skill.id, tali_skill.level, skill.max_level
+                ));
+            }
+
+            if skills[..index]
+                .iter()
+                .any(|other| other.id == tali_skill.id)
+            {
+                return Err(format!("Skill {} is given more than once", tali_skill.id));
+            }
+        }
+
+        if slot_sizes.len() != ARMOR_SLOT_COUNT {
+            return Err(format!(
+                "{} slots instead of {}",
+                slot_sizes.len(),
+                ARMOR_SLOT_COUNT
+            ));
+        }
+
+        for slot_size in slot_sizes {
+            if *slot_size < 0 || (MAX_SLOT_LEVEL as i32) < *slot_size {
+                return Err(format!(
+                    "Slot size {} is out of 0 to {}",
+                    slot_size, MAX_SLOT_LEVEL
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert_talisman_by_slot(
+        slot_only_talismans: &mut HashMap<String, Talisman>,
+        talismans_by_slot: &mut HashMap<String, Vec<Talisman>>,
+        tali: &Talisman,
+    ) {
+        let slot_tali_id = BaseArmor::get_slot_armor_id(&tali.slot_sizes);
+
+        if !slot_only_talismans.contains_key(&slot_tali_id) {
+            slot_only_talismans.insert(
+                slot_tali_id.clone(),
+                Talisman::get_slot_talisman(slot_tali_id.clone()),
+            );
+        }
+
+        talismans_by_slot
+            .entry(slot_tali_id)
+            .or_default()
+            .push(tali.clone());
+    }
+
+    /// Drops the slot only talisman with the last talisman of its slots
+    fn remove_talisman_by_slot(
+        slot_only_talismans: &mut HashMap<String, Talisman>,
+        talismans_by_slot: &mut HashMap<String, Vec<Talisman>>,
+        tali: &Talisman,
+    ) {
+        let slot_tali_id = BaseArmor::get_slot_armor_id(&tali.slot_sizes);
+
+        if let Some(slot_talismans) = talismans_by_slot.get_mut(&slot_tali_id) {
+            slot_talismans.retain(|slot_tali| slot_tali.id() != tali.id());
+
+            if slot_talismans.is_empty() {
+                talismans_by_slot.remove(&slot_tali_id);
+                slot_only_talismans.remove(&slot_tali_id);
+            }
+        }
+    }
+
+    pub fn get_parts(&self, part: ArmorPart) -> Vec<&BaseArmor> {
+        let mut ret = Vec::new();
+
+        for part_armors in self.bases_by_part.get(&part).unwrap() {
+            ret.push(part_armors);
+        }
+
+        for part_anomaly in self.anomalies_by_part.get(&part).unwrap() {
+            ret.push(part_anomaly);
+        }
+
+        ret
+    }
+
+    pub fn get_parts_clone(&self, part: ArmorPart) -> Vec<BaseArmor> {
+        let mut ret = Vec::<BaseArmor>::new();
+
+        for part_armors in self.bases_by_part.get(&part).unwrap() {
+            ret.push(part_armors.clone());
+        }
+
+        for part_anomaly in self.anomalies_by_part.get(&part).unwrap() {
+            ret.push(part_anomaly.clone());
+        }
+
+        ret
+    }
+
+    pub fn get_anomaly_armor(&self, armor_id: &String) -> Option<&AnomalyArmor> {
+        if !BaseArmor::is_anomaly_armor(armor_id) {
+            return None;
+        }
+
+        self.anomaly_armors.get(armor_id)
+    }
+
+    /// Returns the armor with anomaly crafting applied and its original armor
+    pub fn get_armor_by_id(&self, armor_id: &String) -> Option<(&BaseArmor, &BaseArmor)> {
+        if let Some(anomaly) = self.get_anomaly_armor(armor_id) {
+            return Some((&anomaly.affected, &anomaly.original));
+        }
+
+        if let Some(armor) = self.armors.get(armor_id) {
+            return Some((armor, armor));
+        }
+
+        self.empty_armors
+            .values()
+            .find(|armor| armor.id() == armor_id)
+            .map(|armor| (armor, armor))
+    }
+
+    pub fn get_talisman_by_id(&self, tali_id: &String) -> Option<&Talisman> {
+        if self.empty_talisman.id() == tali_id {
+            return Some(&self.empty_talisman);
+        }
+
+        self.talismans.iter().find(|tali| tali.id() == tali_id)
+    }
+
+    pub fn get_deco_by_skill_id(&self, skill_id: &String) -> Vec<&Decoration> {
+        let existing = self.decos_by_skill.get(skill_id);
+
+        match existing {
+            Some(vec) => {
+                let mut ret = Vec::<&Decoration>::new();
+
+                for deco in vec {
+                    ret.push(deco);
+                }
+
+                ret
+            }
+            None => Vec::new(),
+        }
+    }
+
+    pub fn has_decoration(&self, skill_id: &String) -> bool {
+        self.decos_by_skill.contains_key(skill_id)
+    }
+
+    pub fn get_leftover_skills(
+        &self,
+        req_skills: &HashMap<String, i32>,
+    ) -> (HashMap<String, i32>, HashMap<String, i32>) {
+        let mut yes_deco_skills = HashMap::<String, i32>::new();
+        let mut no_deco_skills = HashMap::<String, i32>::new();
+
+        for (skill_id, level) in req_skills {
+            let decos = self.get_deco_by_skill_id(skill_id);
+
+            if !decos.is_empty() {
+                yes_deco_skills.insert(skill_id.clone(), *level);
+            } else {
+                no_deco_skills.insert(skill_id.clone(), *level);
+            }
+        }
+
+        (no_deco_skills, yes_deco_skills)
+    }
+
+    pub fn get_skils_by_deco(
+        &self,
+        req_skills: &HashMap<String, i32>,
+    ) -> (HashMap<String, i32>, SingleDecoSkills, HashMap<String, i32>) {
+        let mut no_deco_skills = HashMap::<String, i32>::new();
+        let mut single_deco_skills = SingleDecoSkills::new();
+        let mut multi_deco_skills = HashMap::<String, i32>::new();
+
+        for (skill_id, level) in req_skills {
+            let decos = self.get_deco_by_skill_id(skill_id);
+
+            if decos.is_empty() {
+                no_deco_skills.insert(skill_id.clone(), *level);
+            } else if decos.len() == 1 {
+                let slot_size = self.decos_by_skill[skill_id][0].slot_size;
+                single_deco_skills.insert(skill_id.clone(), (slot_size, *level));
+            } else {
+                multi_deco_skills.insert(skill_id.clone(), *level);
+            }
+        }
+
+        (no_deco_skills, single_deco_skills, multi_deco_skills)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::DataManager;
+    use crate::{
+        data::armor::{BaseArmor, Talisman, TalismanSkill},
+        parse::parse_talisman,
+        test::get_data_manager,
+    };
+
+    #[test]
+    fn talisman_entry_updates_slot_indexes() {
+        let mut dm = get_data_manager().clone();
+
+        let skill = |id: &str, level: i32| TalismanSkill {
+            id: id.to_string(),
+            level,
+        };
+
+        dm.set_talismans(vec![Talisman::new(
+            "talisman_0".to_string(),
+            vec![skill("critical_eye", 2)],
+            vec![2, 1, 0],
+        )]);
+
+        let slot_2_1_0 = BaseArmor::get_slot_armor_id(&[2, 1, 0]);
+        let slot_3_0_0 = BaseArmor::get_slot_armor_id(&[3, 0, 0]);
+
+        let added = dm
+            .add_talisman(vec![skill("attack_boost", 1)], vec![0, 1, 2])
+            .unwrap();
+
+        assert_eq!(added.id(), "talisman_1");
+        assert_eq!(added.slot_sizes, vec![2, 1, 0]);
+        assert_eq!(dm.talismans_by_slot[&slot_2_1_0].len(), 2);
+
+        let max_level = dm.skills["critical_eye"].max_level;
+
+        for (skills, slot_sizes) in [
+            (vec![skill("critical_eye", max_level + 1)], vec![0, 0, 0]),
+            (vec![skill("critical_eye", 0)], vec![0, 0, 0]),
+            (vec![skill("not_existing", 1)], vec![0, 0, 0]),
+            (
+                vec![skill("critical_eye", 1), skill("critical_eye", 1)],
+                vec![0, 0, 0],
+            ),
+            (vec![skill("critical_eye", 1)], vec![5, 0, 0]),
+            (vec![skill("critical_eye", 1)], vec![1, 0]),
+        ] {
+            assert!(dm.add_talisman(skills.clone(), slot_sizes.clone()).is_err());
+            assert!(dm
+                .edit_talisman(&"talisman_0".to_string(), skills, slot_sizes)
+                .is_err());
+        }
+
+        assert_eq!(dm.talismans.len(), 2);
+        assert_eq!(dm.talismans_by_slot[&slot_2_1_0].len(), 2);
+
+        dm.edit_talisman(
+            &"talisman_0".to_string(),
+            vec![skill("critical_eye", 3)],
+            vec![3, 0, 0],
+        )
+        .unwrap();
+
+        assert_eq!(dm.talismans[0].skills[0].level, 3);
+        assert_eq!(dm.talismans_by_slot[&slot_2_1_0].len(), 1);
+        assert_eq!(dm.talismans_by_slot[&slot_3_0_0][0].id(), "talisman_0");
+        assert!(dm.slot_only_talismans.contains_key(&slot_3_0_0));
+
+        dm.delete_talisman(&"talisman_1".to_string()).unwrap();
+
+        assert_eq!(dm.talismans.len(), 1);
+        assert!(!dm.talismans_by_slot.contains_key(&slot_2_1_0));
+        assert!(!dm.slot_only_talismans.contains_key(&slot_2_1_0));
+        assert!(dm.delete_talisman(&"talisman_1".to_string()).is_err());
+
+        // Ids of deleted talismans are not given again
+        let readded = dm
+            .add_talisman(vec![skill("attack_boost", 1)], vec![1, 0, 0])
+            .unwrap();
+
+        assert_eq!(readded.id(), "talisman_2");
+        dm.delete_talisman(&"talisman_2".to_string()).unwrap();
+
+        // Parsed slot sizes are sorted as the entered ones
+        let filename = std::env::temp_dir().join("mhr_sim_test_talisman_slots.csv");
+        let filename = filename.to_str().unwrap();
+
+        std::fs::write(filename, "Attack Boost,1,,0,0,1,2\n").unwrap();
+
+        let parsed = parse_talisman(filename, &dm.skill_name_dict, Some("en")).unwrap();
+
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(parsed.values[0].slot_sizes, added.slot_sizes);
+
+        // Indexes match a full rebuild from the same list, which renumbers the talismans
+        let get_indexes_by_slot = |dm: &DataManager| {
+            dm.talismans_by_slot
+                .iter()
+                .map(|(slot_tali_id, talismans)| {
+                    let indexes = talismans
+                        .iter()
+                        .map(|tali| {
+                            dm.talismans
+                                .iter()
+                                .position(|other| other.id() == tali.id())
+                                .unwrap()
+                        })
+                        .collect::<Vec<usize>>();
+
+                    (slot_tali_id.clone(), indexes)
+                })
+                .collect::<HashMap<String, Vec<usize>>>()
+        };
+
+        let incremental = get_indexes_by_slot(&dm);
+        dm.set_talismans(dm.talismans.clone());
+
+        assert_eq!(get_indexes_by_slot(&dm), incremental);
+        assert_eq!(dm.talismans_by_slot.len(), dm.slot_only_talismans.len());
+    }
+
+    #[test]
+    fn talisman_reimport_keeps_numbering() {
+        let mut dm = get_data_manager().clone();
+
+        let filename = std::env::temp_dir().join("mhr_sim_test_talisman_reimport.csv");
+        let filename = filename.to_str().unwrap();
+
+        std::fs::write(
+            filename,
+            "Attack Boost,1,,0,1,0,0\nCritical Eye,2,,0,2,1,0\n",
+        )
+        .unwrap();
+
+        let parsed = parse_talisman(filename, &dm.skill_name_dict, Some("en")).unwrap();
+
+        std::fs::remove_file(filename).unwrap();
+
+        let get_ids = |dm: &DataManager| {
+            dm.talismans
+                .iter()
+                .map(|tali| tali.id().clone())
+                .collect::<Vec<String>>()
+        };
+
+        dm.set_talismans(parsed.values.clone());
+
+        assert_eq!(get_ids(&dm), vec!["talisman_0", "talisman_1"]);
+
+        let added = dm
+            .add_talisman(
+                vec![TalismanSkill {
+                    id: "attack_boost".to_string(),
+                    level: 2,
+                }],
+                vec![1, 0, 0],
+            )
+            .unwrap();
+
+        assert_eq!(added.id(), "talisman_2");
+
+        // The same file again, e.g. after editing it, never takes ids of earlier talismans
+        dm.set_talismans(parsed.values);
+
+        assert_eq!(get_ids(&dm), vec!["talisman_3", "talisman_4"]);
+        assert_eq!(dm.talismans[1].skills[0].id, "critical_eye");
+        assert!(dm
+            .talismans_by_slot
+            .values()
+            .flatten()
+            .all(|tali| tali.id() != "talisman_0"));
+
+        let readded = dm
+            .add_talisman(
+                vec![TalismanSkill {
+                    id: "attack_boost".to_string(),
+                    level: 1,
+                }],
+                vec![0, 0, 0],
+            )
+            .unwrap();
+
+        assert_eq!(readded.id(), "talisman_5");
+    }
+}
@@ -0,0 +1,238 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use serde::{de, Serialize};
+use serde_json::Value;
+
+pub static OVERRIDE_DIRNAME: &str = "overrides";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideKind {
+    Armor,
+    Skill,
+    Deco,
+}
+
+impl OverrideKind {
+    /// Fields replaced whole instead of merged, as the crawler applies `armor_override.json`
+    pub fn replaced_keys(&self) -> &'static [&'static str] {
+        match self {
+            OverrideKind::Armor => &["stat", "skills", "slots"],
+            OverrideKind::Skill | OverrideKind::Deco => &[],
+        }
+    }
+}
+
+/// Array of partial armors, skills or decorations, each patching the one with its `id`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverrideFile {
+    pub kind: OverrideKind,
+    pub filename: PathBuf,
+}
+
+impl OverrideFile {
+    /// Kind by the file name prefix, as in `armor_override.json` or `deco_tu4_fix.json`
+    pub fn from_filename(filename: &Path) -> Option<Self> {
+        let name = filename.file_name()?.to_str()?;
+
+        if !name.ends_with(".json") {
+            return None;
+        }
+
+        let kind = if name.starts_with("armor") {
+            OverrideKind::Armor
+        } else if name.starts_with("skill") {
+            OverrideKind::Skill
+        } else if name.starts_with("deco") {
+            OverrideKind::Deco
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            kind,
+            filename: filename.to_path_buf(),
+        })
+    }
+
+    /// Override files of a directory in file name order, none if it doesn't exist
+    pub fn find_in_dir(dir: &Path) -> Vec<Self> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut filenames = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<PathBuf>>();
+
+        filenames.sort();
+
+        filenames
+            .iter()
+            .filter_map(|filename| Self::from_filename(filename))
+            .collect()
+    }
+
+    pub fn read_patches(&self) -> Result<Vec<Value>, String> {
+        let file = File::open(&self.filename)
+            .map_err(|err| format!("Failed to open {:?}: {}", self.filename, err))?;
+
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| format!("Invalid override file {:?}: {}", self.filename, err))
+    }
+}
+
+/// Patches values by id as JSON merge patches (RFC 7386): objects are merged,
+/// `null` removes a key, anything else is replaced. Top-level `replaced_keys` are
+/// replaced whole, e.g. armor skills not listed in the patch are removed.
+/// Invalid patches are skipped and reported, leaving the value as it was.
+pub fn apply_patches<T, F>(
+    values: &mut [T],
+    get_id: F,
+    replaced_keys: &[&str],
+    patches: &[Value],
+) -> Vec<String>
+where
+    T: Serialize + de::DeserializeOwned,
+    F: Fn(&T) -> &String,
+{
+    let mut errors = Vec::new();
+
+    for (index, patch) in patches.iter().enumerate() {
+        let id = match patch.get("id").and_then(|id| id.as_str()) {
+            Some(id) => id,
+            None => {
+                errors.push(format!("#{}: no id", index));
+                continue;
+            }
+        };
+
+        let value = match values.iter_mut().find(|value| get_id(value) == id) {
+            Some(value) => value,
+            None => {
+                errors.push(format!("#{}: unknown id {}", index, id));
+                continue;
+            }
+        };
+
+        let patched = serde_json::to_value(&*value).and_then(|mut json| {
+            if let Some(json_map) = json.as_object_mut() {
+                for key in replaced_keys {
+                    if let Some(patch_value) = patch.get(*key) {
+                        json_map.insert(key.to_string(), Value::Null);
+                        merge_patch(json_map.get_mut(*key).unwrap(), patch_value);
+                    }
+                }
+            }
+
+            merge_patch(&mut json, patch);
+            serde_json::from_value::<T>(json)
+        });
+
+        match patched {
+            Ok(patched) => *value = patched,
+            Err(err) => errors.push(format!("#{} ({}): {}", index, id, err)),
+        }
+    }
+
+    errors
+}
+
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let patch_map = match patch.as_object() {
+        Some(patch_map) => patch_map,
+        None => {
+            *target = patch.clone();
+            return;
+        }
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+
+    let target_map = target.as_object_mut().unwrap();
+
+    for (key, value) in patch_map {
+        if value.is_null() {
+            target_map.remove(key);
+        } else {
+            merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OverrideFile;
+    use crate::{parse::create_data_manager_with_overrides, test::get_data_manager};
+
+    #[test]
+    fn data_overrides_patch_by_id() {
+        let dir = std::env::temp_dir().join("mhr_sim_test_overrides");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(
+            dir.join("armor_override.json"),
+            r#"[
+                {
+                    "id": "silver_solhelm",
+                    "slots": [3, 2, 1],
+                    "skills": {"critical_boost": {"level": 2}, "windproof": {"level": 3}}
+                },
+                {"id": "not_an_armor", "rarity": 1}
+            ]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("skill_override.json"),
+            r#"[{"id": "attack_boost", "maxLevel": 5}, {"id": "critical_eye", "maxLevel": "x"}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("skill_override_2.json"),
+            r#"[{"id": "attack_boost", "maxLevel": 6}]"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "not an override").unwrap();
+
+        let override_files = OverrideFile::find_in_dir(&dir);
+
+        let (dm, report) = create_data_manager_with_overrides(
+            "../src-tauri/data/armor.json",
+            "../src-tauri/data/skill.json",
+            "../src-tauri/data/deco.json",
+            &override_files,
+        )
+        .unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(override_files.len(), 3);
+        let skipped = report
+            .issues
+            .iter()
+            .filter(|issue| issue.kind == "override")
+            .count();
+        assert_eq!(skipped, 2);
+
+        // Skills are replaced whole as by the crawler, so skills left out are removed
+        let helm = &dm.armors["silver_solhelm"];
+        assert_eq!(helm.slots, vec![3, 2, 1]);
+        assert_eq!(helm.skills.len(), 2);
+        assert!(!helm.skills.contains_key("critical_element"));
+        assert_eq!(helm.skills["critical_boost"].level, 2);
+        assert_eq!(helm.names["en"], "Silver Solhelm");
+
+        // Later files win, invalid entries leave the data as it was
+        let original = get_data_manager();
+
+        assert_eq!(dm.skills["attack_boost"].max_level, 6);
+        assert_eq!(
+            dm.skills["critical_eye"].max_level,
+            original.skills["critical_eye"].max_level
+        );
+    }
+}
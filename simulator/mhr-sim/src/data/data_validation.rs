@@ -0,0 +1,358 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::armor::BaseArmor;
+use super::deco::Decoration;
+use super::skill::{Skill, MAX_SLOT_LEVEL};
+
+/// Slots per armor in data, `BaseArmor::get_slot_armor_id` relies on it
+pub(crate) const ARMOR_SLOT_COUNT: usize = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// Data can't be loaded
+    #[serde(rename = "error")]
+    Error,
+    /// Data is loaded, but likely not as intended
+    #[serde(rename = "warning")]
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DataIssue {
+    pub severity: IssueSeverity,
+    /// "armor", "skill", "decoration" or "override"
+    pub kind: String,
+    /// Id of the entry, the file name of overrides
+    pub id: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DataReport {
+    #[serde(rename = "armorCount")]
+    pub armor_count: usize,
+    #[serde(rename = "skillCount")]
+    pub skill_count: usize,
+    #[serde(rename = "decoCount")]
+    pub deco_count: usize,
+
+    pub issues: Vec<DataIssue>,
+}
+
+impl fmt::Display for DataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+        };
+
+        write!(
+            f,
+            "{}: {} {}: {}",
+            severity, self.kind, self.id, self.message
+        )
+    }
+}
+
+impl DataReport {
+    pub fn get_errors(&self) -> Vec<&DataIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .collect()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.get_errors().is_empty()
+    }
+
+    pub fn add(&mut self, severity: IssueSeverity, kind: &str, id: &str, message: String) {
+        self.issues.push(DataIssue {
+            severity,
+            kind: kind.to_string(),
+            id: id.to_string(),
+            message,
+        });
+    }
+}
+
+/// Checks references between armors, skills and decorations and the ranges of their values
+pub fn validate_data(
+    armors: &Vec<BaseArmor>,
+    skills: &Vec<Skill>,
+    decos: &Vec<Decoration>,
+) -> DataReport {
+    let mut report = DataReport {
+        armor_count: armors.len(),
+        skill_count: skills.len(),
+        deco_count: decos.len(),
+        issues: Vec::new(),
+    };
+
+    for (kind, count) in [
+        ("armor", armors.len()),
+        ("skill", skills.len()),
+        ("decoration", decos.len()),
+    ] {
+        if count == 0 {
+            report.add(IssueSeverity::Error, kind, "", "no data".to_string());
+        }
+    }
+
+    check_duplicates(&mut report, "armor", armors.iter().map(|armor| armor.id()));
+    check_duplicates(&mut report, "skill", skills.iter().map(|skill| &skill.id));
+    check_duplicates(&mut report, "decoration", decos.iter().map(|deco| &deco.id));
+
+    let max_levels = skills
+        .iter()
+        .map(|skill| (&skill.id, skill.max_level))
+        .collect::<HashMap<&String, i32>>();
+
+    for skill in skills {
+        if skill.max_level < 1 {
+            report.add(
+                IssueSeverity::Error,
+                "skill",
+                &skill.id,
+                format!("max level {} is less than 1", skill.max_level),
+            );
+        }
+
+        if skill.names.is_empty() {
+            report.add(
+                IssueSeverity::Warning,
+                "skill",
+                &skill.id,
+                "no names".to_string(),
+            );
+        }
+    }
+
+    for armor in armors {
+        let id = armor.id();
+
+        if armor.slots.len() != ARMOR_SLOT_COUNT {
+            report.add(
+                IssueSeverity::Error,
+                "armor",
+                id,
+                format!(
+                    "{} slots instead of {}",
+                    armor.slots.len(),
+                    ARMOR_SLOT_COUNT
+                ),
+            );
+        }
+
+        for slot_size in &armor.slots {
+            if *slot_size < 0 || (MAX_SLOT_LEVEL as i32) < *slot_size {
+                report.add(
+                    IssueSeverity::Error,
+                    "armor",
+                    id,
+                    format!("slot size {} is out of 0 to {}", slot_size, MAX_SLOT_LEVEL),
+                );
+            }
+        }
+
+        let mut skill_ids = armor.skills.keys().collect::<Vec<&String>>();
+        skill_ids.sort();
+
+        for skill_id in skill_ids {
+            check_skill_level(
+                &mut report,
+                "armor",
+                id,
+                &max_levels,
+                skill_id,
+                armor.skills[skill_id].level,
+            );
+        }
+
+        if armor.names.is_empty() {
+            report.add(IssueSeverity::Warning, "armor", id, "no names".to_string());
+        }
+    }
+
+    for deco in decos {
+        if deco.slot_size < 1 || (MAX_SLOT_LEVEL as i32) < deco.slot_size {
+            report.add(
+                IssueSeverity::Error,
+                "decoration",
+                &deco.id,
+                format!(
+                    "slot size {} is out of 1 to {}",
+                    deco.slot_size, MAX_SLOT_LEVEL
+                ),
+            );
+        }
+
+        check_skill_level(
+            &mut report,
+            "decoration",
+            &deco.id,
+            &max_levels,
+            &deco.skill_id,
+            deco.skill_level,
+        );
+
+        if deco.names.is_empty() {
+            report.add(
+                IssueSeverity::Warning,
+                "decoration",
+                &deco.id,
+                "no names".to_string(),
+            );
+        }
+    }
+
+    report
+}
+
+fn check_duplicates<'a, I>(report: &mut DataReport, kind: &str, ids: I)
+where
+    I: Iterator<Item = &'a String>,
+{
+    let mut existing = HashSet::new();
+
+    for id in ids {
+        if !existing.insert(id) {
+            report.add(IssueSeverity::Error, kind, id, "duplicate id".to_string());
+        }
+    }
+}
+
+fn check_skill_level(
+    report: &mut DataReport,
+    kind: &str,
+    id: &str,
+    max_levels: &HashMap<&String, i32>,
+    skill_id: &String,
+    level: i32,
+) {
+    let max_level = match max_levels.get(skill_id) {
+        Some(max_level) => *max_level,
+        None => {
+            report.add(
+                IssueSeverity::Error,
+                kind,
+                id,
+                format!("unknown skill {}", skill_id),
+            );
+            return;
+        }
+    };
+
+    if level < 1 {
+        report.add(
+            IssueSeverity::Error,
+            kind,
+            id,
+            format!("skill {} level {} is less than 1", skill_id, level),
+        );
+    } else if max_level < level {
+        // Levels are capped to the max level in search, likely the max level is outdated instead
+        report.add(
+            IssueSeverity::Warning,
+            kind,
+            id,
+            format!(
+                "skill {} level {} is above its max level {}",
+                skill_id, level, max_level
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IssueSeverity;
+    use crate::{
+        data::data_override::OverrideFile,
+        parse::{create_data_manager_with_overrides, load_data},
+    };
+
+    #[test]
+    fn data_validation_reports_broken_data() {
+        let dir = std::env::temp_dir().join("mhr_sim_test_validation");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(
+            dir.join("armor_broken.json"),
+            r#"[{"id": "silver_solhelm", "slots": [5, 0, 0], "skills": {"critical_boost": {"level": 0}}}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("deco_broken.json"),
+            r#"[{"id": "mastery_jewel_2_1", "skillId": "not_a_skill"}]"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("skill.json"), r#"[{"id": "attack_boost""#).unwrap();
+
+        let override_files = OverrideFile::find_in_dir(&dir);
+
+        let loaded = load_data(
+            "../src-tauri/data/armor.json",
+            "../src-tauri/data/skill.json",
+            "../src-tauri/data/deco.json",
+            &override_files,
+        )
+        .unwrap();
+
+        let errors = loaded
+            .report
+            .get_errors()
+            .iter()
+            .map(|issue| (issue.kind.as_str(), issue.id.as_str()))
+            .collect::<Vec<(&str, &str)>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                ("armor", "silver_solhelm"),
+                ("armor", "silver_solhelm"),
+                ("decoration", "mastery_jewel_2_1"),
+            ]
+        );
+
+        // Used to panic building decoration combinations
+        let result = create_data_manager_with_overrides(
+            "../src-tauri/data/armor.json",
+            "../src-tauri/data/skill.json",
+            "../src-tauri/data/deco.json",
+            &override_files,
+        );
+        match result {
+            Ok(_) => panic!("Broken data loaded"),
+            Err(message) => assert!(message.starts_with("Invalid data, 3 error(s)")),
+        }
+
+        // Used to load as no skills
+        let result = load_data(
+            "../src-tauri/data/armor.json",
+            dir.join("skill.json").to_str().unwrap(),
+            "../src-tauri/data/deco.json",
+            &Vec::new(),
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+
+        let report = create_data_manager_with_overrides(
+            "../src-tauri/data/armor.json",
+            "../src-tauri/data/skill.json",
+            "../src-tauri/data/deco.json",
+            &Vec::new(),
+        )
+        .unwrap()
+        .1;
+        assert!(report
+            .issues
+            .iter()
+            .all(|issue| issue.severity == IssueSeverity::Warning));
+    }
+}
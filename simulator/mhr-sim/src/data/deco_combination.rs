@@ -1,13 +1,15 @@
 use std::collections::HashMap;
 
 use itertools::izip;
-use log::debug;
 
 use crate::data::{deco::Decoration, skill::Skill};
 
 use super::skill::MAX_SLOT_LEVEL;
 
-#[derive(Default)]
+/// Combinations for a skill level, each as decoration counts per slot size
+type LevelCombinations = Vec<Vec<i32>>;
+
+#[derive(Default, Clone)]
 pub struct DecorationCombinations {
     pub combinations: HashMap<String, Vec<Vec<Vec<i32>>>>,
 }
@@ -18,15 +20,48 @@ pub struct DecorationCombination {
     pub sum: Vec<i32>,
 }
 
+/// The highest level decoration of each slot size, sorted by slot size.
+/// Combinations are made of these, a lower level decoration of the same size never takes fewer slots.
+/// Owned lower level ones are still used if they are enough, see `DecoInventory::get_owned_decos`.
+pub fn get_best_decos(decos: &Vec<Decoration>) -> Vec<&Decoration> {
+    let mut best_decos = Vec::<&Decoration>::new();
+
+    for deco in decos {
+        match best_decos
+            .iter_mut()
+            .find(|best| best.slot_size == deco.slot_size)
+        {
+            Some(best) => {
+                if best.skill_level < deco.skill_level {
+                    *best = deco;
+                }
+            }
+            None => best_decos.push(deco),
+        }
+    }
+
+    best_decos.sort_by_key(|deco| deco.slot_size);
+
+    best_decos
+}
+
+fn get_level_sum(decos: &Vec<&Decoration>, comb: &Vec<i32>) -> i32 {
+    izip!(decos, comb)
+        .map(|(deco, count)| deco.skill_level * count)
+        .sum()
+}
+
 impl DecorationCombinations {
     pub fn new(
         decos_by_skill: &HashMap<String, Vec<Decoration>>,
         skills: &HashMap<String, Skill>,
-    ) -> DecorationCombinations {
+    ) -> Result<DecorationCombinations, String> {
         let mut combinations = HashMap::<String, Vec<Vec<Vec<i32>>>>::new();
 
         for (id, decos) in decos_by_skill {
-            let skill = skills.get(id).unwrap();
+            let skill = skills
+                .get(id)
+                .ok_or(format!("Decoration skill {} does not exist", id))?;
             let max_level = skill.max_level;
 
             if decos.len() == 1 {
@@ -48,10 +83,12 @@ impl DecorationCombinations {
             } else {
                 combinations.insert(id.clone(), vec![]);
 
+                let decos = get_best_decos(decos);
+
                 let mut max_deco_counts = Vec::new();
                 let mut init_case = Vec::new();
 
-                for deco in decos {
+                for deco in &decos {
                     let mut max_required = max_level / deco.skill_level + 1;
 
                     if max_level % deco.skill_level == 0 {
@@ -67,21 +104,16 @@ impl DecorationCombinations {
                     let mut skill_done_combs = Vec::new();
 
                     for (slot_size_index, max_deco_count) in max_deco_counts.iter().enumerate() {
-                        let deco = &decos[slot_size_index];
+                        let deco = decos[slot_size_index];
 
                         skill_temp_combs.push(init_case.clone());
                         let deco_temp_combs = skill_temp_combs.clone();
 
                         for temp_comb in &deco_temp_combs {
                             for count in (1..max_deco_count + 1).rev() {
-                                let mut cur_level_sum: i32 = temp_comb
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(prev_level_index, count)| {
-                                        (prev_level_index + 1) as i32 * count
-                                    })
-                                    .sum();
+                                let temp_level_sum = get_level_sum(&decos, temp_comb);
 
+                                let mut cur_level_sum = temp_level_sum;
                                 cur_level_sum += count * deco.skill_level;
 
                                 let mut next_temp_comb = temp_comb.clone();
@@ -90,10 +122,8 @@ impl DecorationCombinations {
                                 if req_level <= cur_level_sum {
                                     let mut has_better_slot_answer = false;
 
-                                    for lower_deco_size in 0..slot_size_index {
-                                        let lower_deco = &decos[lower_deco_size];
-
-                                        let mut lower_level_sum: i32 = temp_comb.iter().sum();
+                                    for lower_deco in &decos[..slot_size_index] {
+                                        let mut lower_level_sum = temp_level_sum;
                                         lower_level_sum += count * lower_deco.skill_level;
 
                                         if req_level <= lower_level_sum {
@@ -102,7 +132,7 @@ impl DecorationCombinations {
                                         }
                                     }
 
-                                    if has_better_slot_answer == false {
+                                    if !has_better_slot_answer {
                                         skill_done_combs.push(next_temp_comb);
                                     }
                                 } else {
@@ -121,11 +151,14 @@ impl DecorationCombinations {
             for deco_size_combs in combs {
                 let mut remove_comb_indices = Vec::new();
 
-                'remove_loop: for index1 in 0..deco_size_combs.len() - 1 {
+                'remove_loop: for index1 in 0..deco_size_combs.len() {
                     let deco_comb1 = &deco_size_combs[index1];
 
-                    for index2 in index1 + 1..deco_size_combs.len() {
-                        let deco_comb2 = &deco_size_combs[index2];
+                    for (index2, deco_comb2) in deco_size_combs.iter().enumerate() {
+                        // Keeps the last of equal combinations
+                        if index1 == index2 || (deco_comb1 == deco_comb2 && index2 < index1) {
+                            continue;
+                        }
 
                         let mut is_inferior = true;
 
@@ -154,22 +187,18 @@ impl DecorationCombinations {
             .map(|(skill_id, combs_per_skill)| {
                 let mut ret = Vec::new();
 
-                let decos = &decos_by_skill[skill_id];
+                let decos = get_best_decos(&decos_by_skill[skill_id]);
 
                 for combs_per_level in combs_per_skill {
                     let mut converted_level_combs = Vec::new();
 
                     for comb in combs_per_level {
-                        let mut converted = Vec::new();
-                        for _ in 0..MAX_SLOT_LEVEL {
-                            converted.push(0);
-                        }
+                        let mut converted = vec![0; MAX_SLOT_LEVEL];
 
                         for (deco_index, slot_count) in comb.iter().enumerate() {
-                            let deco = &decos[deco_index];
-                            let slot_size = deco.slot_size;
+                            let slot_size = decos[deco_index].slot_size;
 
-                            converted[(slot_size - 1) as usize] = *slot_count;
+                            converted[(slot_size - 1) as usize] += *slot_count;
                         }
 
                         converted_level_combs.push(converted);
@@ -182,7 +211,7 @@ impl DecorationCombinations {
             })
             .collect();
 
-        DecorationCombinations { combinations }
+        Ok(DecorationCombinations { combinations })
     }
 
     pub fn get(&self, skill_id: &String) -> Option<&Vec<Vec<Vec<i32>>>> {
@@ -200,22 +229,22 @@ impl DecorationCombinations {
         &self,
         req_skills: &HashMap<String, i32>,
     ) -> Vec<DecorationCombination> {
-        if req_skills.len() == 0 {
+        if req_skills.is_empty() {
             return Vec::new();
         }
 
         let mut all_possible_combs = Vec::<DecorationCombination>::new();
 
-        let (skill_ids, combs_per_skill, mut level_indices) = self.get_iter_init_data(&req_skills);
+        let (skill_ids, combs_per_skill, mut level_indices) = self.get_iter_init_data(req_skills);
 
         loop {
-            let deco_comb = self.get_next_deco_comb(&req_skills, &skill_ids, &level_indices);
+            let deco_comb = self.get_next_deco_comb(req_skills, &skill_ids, &level_indices);
 
             all_possible_combs.push(deco_comb);
 
             let proceeded = self.proceed_next_iter(&mut level_indices, &combs_per_skill);
 
-            if proceeded == false {
+            if !proceeded {
                 break;
             }
         }
@@ -228,7 +257,7 @@ impl DecorationCombinations {
         req_skills: &HashMap<String, i32>,
         armor_slots: &Vec<i32>,
     ) -> bool {
-        self.iter_possible_combs(&req_skills, |deco_comb| {
+        self.iter_possible_combs(req_skills, |deco_comb| {
             DecorationCombination::is_possible_static(armor_slots, &deco_comb.sum)
         })
     }
@@ -237,27 +266,27 @@ impl DecorationCombinations {
     where
         F: FnMut(DecorationCombination) -> bool,
     {
-        if req_skills.len() == 0 {
+        if req_skills.is_empty() {
             return true;
         }
 
-        let (skill_ids, combs_per_skill, mut level_indices) = self.get_iter_init_data(&req_skills);
+        let (skill_ids, combs_per_skill, mut level_indices) = self.get_iter_init_data(req_skills);
 
         let mut ret = false;
 
         loop {
-            let deco_comb = self.get_next_deco_comb(&req_skills, &skill_ids, &level_indices);
+            let deco_comb = self.get_next_deco_comb(req_skills, &skill_ids, &level_indices);
 
             let result = f(deco_comb);
 
-            if result == true {
+            if result {
                 ret = true;
                 break;
             }
 
             let proceeded = self.proceed_next_iter(&mut level_indices, &combs_per_skill);
 
-            if proceeded == false {
+            if !proceeded {
                 break;
             }
         }
@@ -268,11 +297,10 @@ impl DecorationCombinations {
     fn get_iter_init_data<'a>(
         &self,
         req_skills: &'a HashMap<String, i32>,
-    ) -> (Vec<&'a std::string::String>, Vec<Vec<Vec<i32>>>, Vec<usize>) {
-        let skill_ids = req_skills
-            .iter()
-            .map(|(skill_id, _)| skill_id)
-            .collect::<Vec<&String>>();
+    ) -> (Vec<&'a String>, Vec<LevelCombinations>, Vec<usize>) {
+        let mut skill_ids = req_skills.keys().collect::<Vec<&String>>();
+
+        skill_ids.sort();
 
         let mut combs_per_skill = Vec::new();
 
@@ -283,11 +311,7 @@ impl DecorationCombinations {
             combs_per_skill.push(combs);
         }
 
-        let mut level_indices = Vec::<usize>::new();
-
-        for _ in &combs_per_skill {
-            level_indices.push(0);
-        }
+        let level_indices = vec![0; combs_per_skill.len()];
 
         (skill_ids, combs_per_skill, level_indices)
     }
@@ -295,14 +319,10 @@ impl DecorationCombinations {
     fn get_next_deco_comb(
         &self,
         req_skills: &HashMap<String, i32>,
-        skill_ids: &Vec<&String>,
-        level_indices: &Vec<usize>,
+        skill_ids: &[&String],
+        level_indices: &[usize],
     ) -> DecorationCombination {
-        let mut slot_combs = Vec::new();
-
-        for _ in 0..MAX_SLOT_LEVEL {
-            slot_combs.push(0);
-        }
+        let mut slot_combs = vec![0; MAX_SLOT_LEVEL];
 
         let mut all_skill_combs = HashMap::new();
 
@@ -326,8 +346,8 @@ impl DecorationCombinations {
 
     fn proceed_next_iter(
         &self,
-        level_indices: &mut Vec<usize>,
-        combs_per_skill: &Vec<Vec<Vec<i32>>>,
+        level_indices: &mut [usize],
+        combs_per_skill: &[Vec<Vec<i32>>],
     ) -> bool {
         let mut promote = 0;
 
@@ -355,7 +375,7 @@ impl DecorationCombinations {
             return slot1.cmp(slot2);
         }
 
-        return std::cmp::Ordering::Equal;
+        std::cmp::Ordering::Equal
     }
 }
 
@@ -411,3 +431,176 @@ impl DecorationCombination {
         promote == 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use super::DecorationCombination;
+    use crate::test::get_data_manager;
+
+    /// Whether each required slot gets a free slot at least as large, by augmenting paths.
+    /// Both are counts per slot size.
+    fn fits_by_matching(free_slots: &Vec<i32>, req_slots: &Vec<i32>) -> bool {
+        let expand = |counts: &Vec<i32>| {
+            counts
+                .iter()
+                .enumerate()
+                .flat_map(|(index, count)| (0..*count).map(move |_| index + 1))
+                .collect::<Vec<usize>>()
+        };
+
+        let free_sizes = expand(free_slots);
+        let req_sizes = expand(req_slots);
+
+        // Required slot matched to each free slot
+        let mut matched = vec![None; free_sizes.len()];
+
+        fn augment(
+            req: usize,
+            free_sizes: &Vec<usize>,
+            req_sizes: &Vec<usize>,
+            matched: &mut Vec<Option<usize>>,
+            visited: &mut Vec<bool>,
+        ) -> bool {
+            for free in 0..free_sizes.len() {
+                if visited[free] || free_sizes[free] < req_sizes[req] {
+                    continue;
+                }
+
+                visited[free] = true;
+
+                let is_available = match matched[free] {
+                    Some(other) => augment(other, free_sizes, req_sizes, matched, visited),
+                    None => true,
+                };
+
+                if is_available {
+                    matched[free] = Some(req);
+                    return true;
+                }
+            }
+
+            false
+        }
+
+        (0..req_sizes.len()).all(|req| {
+            let mut visited = vec![false; free_sizes.len()];
+
+            augment(req, &free_sizes, &req_sizes, &mut matched, &mut visited)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn slot_fitting_matches_oracle(
+            free_slots in vec(0..5, 4),
+            req_slots in vec(0..5, 4),
+            extra_slots in vec(0..3, 4),
+        ) {
+            let expected = fits_by_matching(&free_slots, &req_slots);
+
+            prop_assert_eq!(
+                DecorationCombination::is_possible_static(&free_slots, &req_slots),
+                expected
+            );
+
+            let mut left_slots = free_slots.clone();
+
+            prop_assert_eq!(
+                DecorationCombination::is_possible_static_mut(&mut left_slots, &mut req_slots.clone()),
+                expected
+            );
+
+            // Slots left are as good as the free ones minus the required ones for any later requirement
+            if expected {
+                let all_req_slots = req_slots
+                    .iter()
+                    .zip(extra_slots.iter())
+                    .map(|(req, extra)| req + extra)
+                    .collect::<Vec<i32>>();
+
+                prop_assert_eq!(
+                    fits_by_matching(&left_slots, &extra_slots),
+                    fits_by_matching(&free_slots, &all_req_slots)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn deco_combinations_are_sufficient_and_minimal() {
+        let dm = get_data_manager();
+
+        for (skill_id, decos) in &dm.decos_by_skill {
+            let combs_per_level = dm.deco_combinations.get(skill_id).unwrap();
+            let max_level = dm.skills[skill_id].max_level;
+
+            assert_eq!(combs_per_level.len(), max_level as usize, "{}", skill_id);
+
+            // Without a decoration inventory, placement puts the highest level decoration of each slot size
+            let get_level = |comb: &Vec<i32>| {
+                comb.iter()
+                    .enumerate()
+                    .map(|(slot_size_index, count)| {
+                        match decos
+                            .iter()
+                            .filter(|deco| deco.slot_size == slot_size_index as i32 + 1)
+                            .map(|deco| deco.skill_level)
+                            .max()
+                        {
+                            Some(deco_level) => count * deco_level,
+                            None => {
+                                assert_eq!(*count, 0, "{}: no decoration for {:?}", skill_id, comb);
+                                0
+                            }
+                        }
+                    })
+                    .sum::<i32>()
+            };
+
+            for (level_index, combs) in combs_per_level.iter().enumerate() {
+                let level = level_index as i32 + 1;
+
+                assert_ne!(combs.len(), 0, "{} level {}", skill_id, level);
+
+                let unique_combs = combs.iter().collect::<HashSet<&Vec<i32>>>();
+                assert_eq!(
+                    unique_combs.len(),
+                    combs.len(),
+                    "{} level {}",
+                    skill_id,
+                    level
+                );
+
+                for comb in combs {
+                    assert!(
+                        level <= get_level(comb),
+                        "{} level {}: {:?} is not enough",
+                        skill_id,
+                        level,
+                        comb
+                    );
+
+                    // Combinations fitting in fewer slots are not enough to drop one,
+                    // as they might need decorations that are not owned
+                    let dominating = combs.iter().find(|other| {
+                        other != &comb && other.iter().zip(comb.iter()).all(|(a, b)| a <= b)
+                    });
+
+                    assert!(
+                        dominating.is_none(),
+                        "{} level {}: {:?} has more decorations than {:?}",
+                        skill_id,
+                        level,
+                        comb,
+                        dominating.unwrap()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// A name given to more than one id
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NameCollision {
+    /// "armor", "skill" or "decoration"
+    pub kind: String,
+    /// Language the ids share the name in, `None` if they only share it across languages
+    pub lang: Option<String>,
+    pub name: String,
+    pub ids: Vec<String>,
+}
+
+/// Ids by language and name, so same names of different languages or ids don't overwrite each other
+#[derive(Debug, Clone, Default)]
+pub struct NameDict {
+    kind: String,
+    ids_by_name: BTreeMap<String, BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl NameDict {
+    /// `names_by_id` gives the names per language of each id
+    pub fn new<'a, I>(kind: &str, names_by_id: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a String, &'a HashMap<String, String>)>,
+    {
+        let mut ids_by_name = BTreeMap::<String, BTreeMap<String, BTreeSet<String>>>::new();
+
+        for (id, names) in names_by_id {
+            for (lang, name) in names {
+                ids_by_name
+                    .entry(name.clone())
+                    .or_default()
+                    .entry(lang.clone())
+                    .or_default()
+                    .insert(id.clone());
+            }
+        }
+
+        Self {
+            kind: kind.to_string(),
+            ids_by_name,
+        }
+    }
+
+    /// Ids with the name in `lang`, or in any language if `None`
+    pub fn get_ids(&self, lang: Option<&str>, name: &str) -> Vec<&String> {
+        let ids_by_lang = match self.ids_by_name.get(name) {
+            Some(ids_by_lang) => ids_by_lang,
+            None => return Vec::new(),
+        };
+
+        let ids = match lang {
+            Some(lang) => match ids_by_lang.get(lang) {
+                Some(ids) => ids.iter().collect(),
+                None => BTreeSet::new(),
+            },
+            None => ids_by_lang
+                .values()
+                .flatten()
+                .collect::<BTreeSet<&String>>(),
+        };
+
+        ids.into_iter().collect()
+    }
+
+    /// Id only if the name is given to a single id
+    pub fn get_id(&self, lang: Option<&str>, name: &str) -> Option<&String> {
+        let ids = self.get_ids(lang, name);
+
+        if ids.len() == 1 {
+            return Some(ids[0]);
+        }
+
+        None
+    }
+
+    /// Known names in `lang`, or in any language if `None`
+    pub fn get_names(&self, lang: Option<&str>) -> Vec<&String> {
+        self.ids_by_name
+            .iter()
+            .filter(|(_, ids_by_lang)| match lang {
+                Some(lang) => ids_by_lang.contains_key(lang),
+                None => true,
+            })
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    pub fn get_collisions(&self) -> Vec<NameCollision> {
+        let mut collisions = Vec::new();
+
+        for (name, ids_by_lang) in &self.ids_by_name {
+            let mut all_ids = BTreeSet::new();
+            let mut max_lang_id_count = 0;
+
+            for (lang, ids) in ids_by_lang {
+                if 1 < ids.len() {
+                    collisions.push(NameCollision {
+                        kind: self.kind.clone(),
+                        lang: Some(lang.clone()),
+                        name: name.clone(),
+                        ids: ids.iter().cloned().collect(),
+                    });
+                }
+
+                all_ids.extend(ids.iter());
+                max_lang_id_count = max_lang_id_count.max(ids.len());
+            }
+
+            // Ids not already colliding within a single language
+            if max_lang_id_count < all_ids.len() {
+                collisions.push(NameCollision {
+                    kind: self.kind.clone(),
+                    lang: None,
+                    name: name.clone(),
+                    ids: all_ids.into_iter().cloned().collect(),
+                });
+            }
+        }
+
+        collisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse::{parse_anomaly, parse_talisman, CsvImportErrorKind},
+        test::get_data_manager,
+    };
+
+    #[test]
+    fn name_lookup_by_language() {
+        let dm = get_data_manager();
+
+        let collisions = dm.get_name_collisions();
+
+        // Snowshear mail and coil share their French name
+        assert!(collisions.iter().any(|collision| collision.kind == "armor"
+            && collision.lang == Some("fr".to_string())
+            && collision.ids == vec!["snowshear_coil", "snowshear_mail"]));
+
+        let snowshear_ids = dm.armor_name_dict.get_ids(None, "Cotte coupe-neige");
+
+        assert_eq!(snowshear_ids.len(), 2);
+
+        let filename = std::env::temp_dir().join("mhr_sim_test_talisman_lang.csv");
+        let filename = filename.to_str().unwrap();
+
+        std::fs::write(filename, "Attack Boost,2,,0,1,0,0\n").unwrap();
+
+        let english = parse_talisman(filename, &dm.skill_name_dict, Some("en")).unwrap();
+        let japanese = parse_talisman(filename, &dm.skill_name_dict, Some("ja")).unwrap();
+
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(english.values.len(), 1);
+        assert_eq!(japanese.values.len(), 0);
+        assert_eq!(japanese.errors[0].kind, CsvImportErrorKind::UnknownSkill);
+
+        let anomaly_filename = std::env::temp_dir().join("mhr_sim_test_anomaly_lang.csv");
+        let anomaly_filename = anomaly_filename.to_str().unwrap();
+
+        std::fs::write(anomaly_filename, "Cotte coupe-neige,10,0,0,0,0,0,1,0,0\n").unwrap();
+
+        let anomalies = parse_anomaly(
+            anomaly_filename,
+            &dm.armors,
+            &dm.armor_name_dict,
+            &dm.skill_name_dict,
+            Some("fr"),
+        )
+        .unwrap();
+
+        std::fs::remove_file(anomaly_filename).unwrap();
+
+        assert_eq!(anomalies.errors[0].kind, CsvImportErrorKind::AmbiguousName);
+    }
+}
@@ -0,0 +1,373 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::data_manager::DataManager;
+use crate::data::deco::Decoration;
+use crate::data::deco_combination::DecorationCombination;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OwnedDeco {
+    pub id: String,
+    pub count: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DecoInventory {
+    /// Owned count per decoration id, decorations not listed are not owned
+    pub counts: HashMap<String, i32>,
+
+    /// Searches as if every decoration were owned, reporting missing ones in the results instead
+    #[serde(rename = "shoppingList", default)]
+    pub shopping_list: bool,
+}
+
+impl DecoInventory {
+    pub fn new(owned_decos: &Vec<OwnedDeco>, shopping_list: bool) -> Self {
+        let mut counts = HashMap::new();
+
+        for owned in owned_decos {
+            *counts.entry(owned.id.clone()).or_insert(0) += owned.count;
+        }
+
+        Self {
+            counts,
+            shopping_list,
+        }
+    }
+
+    pub fn get_count(&self, deco_id: &String) -> i32 {
+        *self.counts.get(deco_id).unwrap_or(&0)
+    }
+
+    /// Whether decorations beyond the owned counts are rejected
+    pub fn limits_search(&self) -> bool {
+        !self.shopping_list
+    }
+
+    /// Owned counts are enough for `count` decorations of a skill with a single decoration
+    pub fn allows_single_deco(&self, dm: &DataManager, skill_id: &String, level: i32) -> bool {
+        if !self.limits_search() {
+            return true;
+        }
+
+        let deco = &dm.decos_by_skill[skill_id][0];
+        let count = (level + deco.skill_level - 1) / deco.skill_level;
+
+        count <= self.get_count(&deco.id)
+    }
+
+    /// Owned counts are enough for the decorations of the skills with multiple decorations,
+    /// each reaching its level in `req_skills`
+    pub fn allows_comb(
+        &self,
+        dm: &DataManager,
+        req_skills: &HashMap<String, i32>,
+        deco_comb: &DecorationCombination,
+    ) -> bool {
+        if !self.limits_search() {
+            return true;
+        }
+
+        deco_comb.combs_per_skill.iter().all(|(skill_id, counts)| {
+            let level = *req_skills.get(skill_id).unwrap_or(&0);
+
+            self.get_owned_decos(dm, skill_id, level, counts).is_some()
+        })
+    }
+
+    /// Owned decorations of a skill filling `counts` per slot size, the highest levels first.
+    /// Combinations are made of the highest level decorations, but lower level ones of
+    /// the same slot size might be owned instead and still be enough.
+    /// `None` if too few are owned or their levels sum up short of `level`.
+    pub fn get_owned_decos<'a>(
+        &self,
+        dm: &'a DataManager,
+        skill_id: &String,
+        level: i32,
+        counts: &[i32],
+    ) -> Option<Vec<&'a Decoration>> {
+        let mut owned_decos = Vec::new();
+
+        for (slot_size_index, count) in counts.iter().enumerate() {
+            let slot_size = slot_size_index as i32 + 1;
+
+            let mut size_decos = dm.decos_by_skill[skill_id]
+                .iter()
+                .filter(|deco| deco.slot_size == slot_size)
+                .collect::<Vec<&Decoration>>();
+
+            size_decos.sort_by_key(|deco| Reverse(deco.skill_level));
+
+            let mut count_left = *count;
+
+            for deco in size_decos {
+                let taken = count_left.min(self.get_count(&deco.id));
+
+                for _ in 0..taken {
+                    owned_decos.push(deco);
+                }
+
+                count_left -= taken;
+            }
+
+            if 0 < count_left {
+                return None;
+            }
+        }
+
+        let level_sum = owned_decos.iter().map(|deco| deco.skill_level).sum::<i32>();
+
+        if level_sum < level {
+            return None;
+        }
+
+        Some(owned_decos)
+    }
+
+    /// Decorations used beyond the owned counts, with the missing count of each
+    pub fn get_missing(&self, used_deco_ids: &Vec<&String>) -> HashMap<String, i32> {
+        let mut used_counts = HashMap::<String, i32>::new();
+
+        for deco_id in used_deco_ids {
+            *used_counts.entry(deco_id.to_string()).or_insert(0) += 1;
+        }
+
+        used_counts
+            .into_iter()
+            .filter_map(|(deco_id, count)| {
+                let missing = count - self.get_count(&deco_id);
+
+                if 0 < missing {
+                    Some((deco_id, missing))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::{DecoInventory, OwnedDeco};
+    use crate::{
+        data::{
+            armor::{ArmorPart, SexType},
+            deco_combination::get_best_decos,
+        },
+        search::{search, SearchRequest, SearchResult},
+        test::{create_pinned_request, get_data_manager, get_equip_ids},
+    };
+
+    #[test]
+    fn deco_inventory_limits_search() {
+        let dm = get_data_manager();
+
+        let mut request =
+            create_pinned_request(&[("critical_boost", 3), ("agitator", 3), ("attack_boost", 4)]);
+
+        request.limits.max_results = 100000;
+
+        let unlimited = search(dm, &request).unwrap();
+
+        let owned_decos = vec![
+            OwnedDeco {
+                id: "attack_jewel_2_1".to_string(),
+                count: 1,
+            },
+            OwnedDeco {
+                id: "critical_jewel_2_1".to_string(),
+                count: 3,
+            },
+        ];
+
+        request.deco_inventory = Some(DecoInventory::new(&owned_decos, false));
+
+        let limited = search(dm, &request).unwrap();
+
+        assert_ne!(limited.full_equipments.len(), 0);
+        assert!(limited.full_equipments.len() < unlimited.full_equipments.len());
+
+        for equip in &limited.full_equipments {
+            for deco_comb in &equip.deco_combs {
+                assert_eq!(deco_comb.missing_decos.len(), 0);
+
+                let attack_count = deco_comb
+                    .placement
+                    .iter()
+                    .filter(|slot| slot.deco_id == Some("attack_jewel_2_1".to_string()))
+                    .count();
+
+                assert!(attack_count <= 1);
+            }
+        }
+
+        request.deco_inventory = Some(DecoInventory::new(&owned_decos, true));
+
+        let shopping_list = search(dm, &request).unwrap();
+
+        assert_eq!(
+            shopping_list.full_equipments.len(),
+            unlimited.full_equipments.len()
+        );
+        assert!(shopping_list.full_equipments.iter().any(|equip| equip
+            .deco_combs
+            .iter()
+            .any(|deco_comb| deco_comb.missing_decos.contains_key("challenger_jewel_2_1"))));
+    }
+
+    #[test]
+    fn deco_inventory_pages_are_full() {
+        let dm = get_data_manager();
+
+        let mut request =
+            create_pinned_request(&[("critical_boost", 3), ("agitator", 3), ("attack_boost", 4)]);
+
+        let owned_decos = vec![
+            OwnedDeco {
+                id: "attack_jewel_2_1".to_string(),
+                count: 1,
+            },
+            OwnedDeco {
+                id: "critical_jewel_2_1".to_string(),
+                count: 3,
+            },
+        ];
+
+        request.deco_inventory = Some(DecoInventory::new(&owned_decos, false));
+        request.limits.max_results = 100000;
+
+        let all_ids = search(dm, &request)
+            .unwrap()
+            .full_equipments
+            .iter()
+            .map(get_equip_ids)
+            .collect::<Vec<String>>();
+
+        assert!(3 <= all_ids.len());
+
+        // Rejected answers must not count toward a page, or pages end early without a cursor
+        request.limits.max_results = (all_ids.len() + 2) / 3;
+
+        let mut paged_ids = Vec::new();
+
+        loop {
+            let page = search(dm, &request).unwrap();
+
+            paged_ids.extend(page.full_equipments.iter().map(get_equip_ids));
+
+            match page.next_cursor {
+                Some(cursor) => {
+                    assert_eq!(page.full_equipments.len(), request.limits.max_results);
+                    assert_eq!(cursor, paged_ids.len());
+
+                    request.cursor = Some(cursor);
+                }
+                None => break,
+            }
+        }
+
+        assert_eq!(paged_ids, all_ids);
+    }
+
+    #[test]
+    fn deco_inventory_allows_lower_level_decos() {
+        let dm = get_data_manager();
+
+        // Steadiness has sniper_jewel_1_1, sniper_jewel_4_2 and hard_sniper_jewel_4_3
+        let best_ids = get_best_decos(&dm.decos_by_skill["steadiness"])
+            .iter()
+            .map(|deco| deco.id.clone())
+            .collect::<Vec<String>>();
+
+        assert_eq!(best_ids, vec!["sniper_jewel_1_1", "hard_sniper_jewel_4_3"]);
+
+        let level2_combs = dm
+            .deco_combinations
+            .get_by_level(&"steadiness".to_string(), 1)
+            .into_iter()
+            .collect::<HashSet<Vec<i32>>>();
+
+        assert_eq!(
+            level2_combs,
+            HashSet::from([vec![2, 0, 0, 0], vec![0, 0, 0, 1]])
+        );
+
+        let mut selected_skills = HashMap::<String, i32>::new();
+        selected_skills.insert("steadiness".to_string(), 2);
+
+        let mut request = SearchRequest::new(
+            vec![0, 0, 0],
+            selected_skills,
+            vec![0, 0, 0, 0],
+            SexType::All,
+        );
+
+        for (part, id) in [
+            (ArmorPart::Helm, "silver_solhelm"),
+            (ArmorPart::Torso, "silver_solmail"),
+            (ArmorPart::Arm, "silver_solbraces"),
+            (ArmorPart::Waist, "silver_solcoil"),
+            (ArmorPart::Feet, "silver_solgreaves"),
+        ] {
+            request
+                .constraints
+                .pinned_armors
+                .insert(part, id.to_string());
+        }
+
+        // Placed decorations of each combination, with the number of missing ones
+        let get_deco_ids = |result: &SearchResult| {
+            result
+                .full_equipments
+                .iter()
+                .flat_map(|equip| equip.deco_combs.iter())
+                .map(|deco_comb| {
+                    let deco_ids = deco_comb
+                        .placement
+                        .iter()
+                        .filter_map(|slot| slot.deco_id.clone())
+                        .collect::<Vec<String>>();
+
+                    (deco_ids, deco_comb.missing_decos.len())
+                })
+                .collect::<Vec<(Vec<String>, usize)>>()
+        };
+
+        // A lower level decoration of the best slot size is enough for level 2
+        let owned_decos = vec![OwnedDeco {
+            id: "sniper_jewel_4_2".to_string(),
+            count: 1,
+        }];
+
+        request.deco_inventory = Some(DecoInventory::new(&owned_decos, false));
+
+        let owned_lower = search(dm, &request).unwrap();
+
+        assert_eq!(
+            get_deco_ids(&owned_lower),
+            vec![(vec!["sniper_jewel_4_2".to_string()], 0)]
+        );
+
+        // Shopping lists prefer owned decorations over missing higher level ones
+        request.deco_inventory = Some(DecoInventory::new(&owned_decos, true));
+
+        let shopping_list = search(dm, &request).unwrap();
+
+        assert!(get_deco_ids(&shopping_list).contains(&(vec!["sniper_jewel_4_2".to_string()], 0)));
+
+        // One level 1 decoration is short of level 2
+        let owned_decos = vec![OwnedDeco {
+            id: "sniper_jewel_1_1".to_string(),
+            count: 1,
+        }];
+
+        request.deco_inventory = Some(DecoInventory::new(&owned_decos, false));
+
+        assert_eq!(search(dm, &request).unwrap().full_equipments.len(), 0);
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::data_manager::DataManager;
+use crate::data::deco::Decoration;
+use crate::data::deco_combination::{get_best_decos, DecorationCombination};
+use crate::deco_inventory::DecoInventory;
+use crate::BoxCalcEquipment;
+
+pub static WEAPON_PART: &str = "weapon";
+
+/// A slot of the weapon, an armor part or the talisman, with the decoration put in it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResultDecorationSlot {
+    /// "weapon", an armor part or "talisman"
+    pub part: String,
+    /// Index among the slots of the part, larger slots first
+    pub slot_index: usize,
+    pub slot_size: i32,
+    /// `None` for a slot left empty
+    pub deco_id: Option<String>,
+}
+
+/// Puts decorations required by `deco_comb` (and by skills with a single decoration)
+/// into the slots of the equipments, keeping `free_slots` empty.
+///
+/// Owned decorations are used if they are enough, the highest level ones otherwise.
+///
+/// Larger decorations go first, each into the smallest empty slot it fits,
+/// which always finds a placement if the slot sizes sum up.
+pub fn place_decorations(
+    dm: &DataManager,
+    selected_skills: &HashMap<String, i32>,
+    free_slots: &[i32],
+    weapon_slots: &[i32],
+    equipments: &Vec<BoxCalcEquipment>,
+    deco_comb: &DecorationCombination,
+    deco_inventory: Option<&DecoInventory>,
+) -> Result<Vec<ResultDecorationSlot>, String> {
+    let decos = get_required_decos(dm, selected_skills, equipments, deco_comb, deco_inventory)?;
+
+    // Reserved free slots are placed as empty decorations
+    let mut items = decos
+        .iter()
+        .map(|deco| (deco.slot_size, Some(*deco)))
+        .collect::<Vec<(i32, Option<&Decoration>)>>();
+
+    for (slot_size_index, count) in free_slots.iter().enumerate() {
+        for _ in 0..*count {
+            items.push((slot_size_index as i32 + 1, None));
+        }
+    }
+
+    items.sort_by(|(size1, deco1), (size2, deco2)| {
+        size2
+            .cmp(size1)
+            .then_with(|| deco1.map(|deco| &deco.id).cmp(&deco2.map(|deco| &deco.id)))
+    });
+
+    let mut slots = get_all_slots(weapon_slots, equipments);
+    let mut is_taken = vec![false; slots.len()];
+
+    for (item_size, deco) in items {
+        let mut best_index: Option<usize> = None;
+
+        for (index, slot) in slots.iter().enumerate() {
+            if is_taken[index] || slot.slot_size < item_size {
+                continue;
+            }
+
+            if best_index.is_none() || slot.slot_size < slots[best_index.unwrap()].slot_size {
+                best_index = Some(index);
+            }
+        }
+
+        let index = match best_index {
+            Some(index) => index,
+            None => {
+                return Err(match deco {
+                    Some(deco) => format!("No slot left for decoration {}", deco.id),
+                    None => format!("No free slot left of size {}", item_size),
+                })
+            }
+        };
+
+        is_taken[index] = true;
+        slots[index].deco_id = deco.map(|deco| deco.id.clone());
+    }
+
+    check_placement(dm, selected_skills, equipments, &slots)?;
+
+    Ok(slots)
+}
+
+/// Verifies every decoration fits its slot and the placed decorations complete the skills
+pub fn check_placement(
+    dm: &DataManager,
+    selected_skills: &HashMap<String, i32>,
+    equipments: &Vec<BoxCalcEquipment>,
+    slots: &Vec<ResultDecorationSlot>,
+) -> Result<(), String> {
+    let mut req_skills = selected_skills.clone();
+
+    for equipment in equipments {
+        equipment.subtract_skills(&mut req_skills);
+    }
+
+    for slot in slots {
+        let deco_id = match &slot.deco_id {
+            Some(deco_id) => deco_id,
+            None => continue,
+        };
+
+        let deco = match dm.decos.get(deco_id) {
+            Some(deco) => deco,
+            None => return Err(format!("Unknown decoration {}", deco_id)),
+        };
+
+        if slot.slot_size < deco.slot_size {
+            return Err(format!(
+                "Decoration {} (size {}) does not fit {} slot {} (size {})",
+                deco.id, deco.slot_size, slot.part, slot.slot_index, slot.slot_size
+            ));
+        }
+
+        if let Some(level) = req_skills.get_mut(&deco.skill_id) {
+            *level -= deco.skill_level;
+        }
+    }
+
+    for (skill_id, level) in &req_skills {
+        if 0 < *level {
+            return Err(format!("Skill {} is short of {} levels", skill_id, level));
+        }
+    }
+
+    Ok(())
+}
+
+fn get_required_decos<'a>(
+    dm: &'a DataManager,
+    selected_skills: &HashMap<String, i32>,
+    equipments: &Vec<BoxCalcEquipment>,
+    deco_comb: &DecorationCombination,
+    deco_inventory: Option<&DecoInventory>,
+) -> Result<Vec<&'a Decoration>, String> {
+    let mut req_skills = selected_skills.clone();
+
+    for equipment in equipments {
+        equipment.subtract_skills(&mut req_skills);
+    }
+
+    let mut skill_ids = req_skills.keys().collect::<Vec<&String>>();
+    skill_ids.sort();
+
+    let mut ret = Vec::new();
+
+    for skill_id in skill_ids {
+        let level = req_skills[skill_id];
+        let decos = dm.get_deco_by_skill_id(skill_id);
+
+        if decos.is_empty() {
+            return Err(format!("Skill {} has no decoration", skill_id));
+        }
+
+        if decos.len() == 1 {
+            let deco = decos[0];
+            let count = (level + deco.skill_level - 1) / deco.skill_level;
+
+            for _ in 0..count {
+                ret.push(deco);
+            }
+
+            continue;
+        }
+
+        let counts = match deco_comb.combs_per_skill.get(skill_id) {
+            Some(counts) => counts,
+            None => return Err(format!("No decoration combination for skill {}", skill_id)),
+        };
+
+        let owned_decos = deco_inventory
+            .and_then(|deco_inventory| deco_inventory.get_owned_decos(dm, skill_id, level, counts));
+
+        if let Some(mut owned_decos) = owned_decos {
+            ret.append(&mut owned_decos);
+            continue;
+        }
+
+        let best_decos = get_best_decos(&dm.decos_by_skill[skill_id]);
+
+        for (slot_size_index, count) in counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+
+            let slot_size = slot_size_index as i32 + 1;
+
+            let deco = match best_decos.iter().find(|deco| deco.slot_size == slot_size) {
+                Some(deco) => *deco,
+                None => {
+                    return Err(format!(
+                        "Skill {} has no decoration of size {}",
+                        skill_id, slot_size
+                    ))
+                }
+            };
+
+            for _ in 0..*count {
+                ret.push(deco);
+            }
+        }
+    }
+
+    Ok(ret)
+}
+
+pub(crate) fn get_all_slots(
+    weapon_slots: &[i32],
+    equipments: &Vec<BoxCalcEquipment>,
+) -> Vec<ResultDecorationSlot> {
+    let mut slots = Vec::new();
+
+    let mut weapon_slot_sizes = weapon_slots
+        .iter()
+        .filter(|size| 0 < **size)
+        .cloned()
+        .collect::<Vec<i32>>();
+
+    weapon_slot_sizes.sort_by(|a, b| b.cmp(a));
+
+    push_slots(&mut slots, WEAPON_PART, &weapon_slot_sizes);
+
+    for equipment in equipments {
+        // Slots of equipments are counts per slot size
+        let mut slot_sizes = Vec::new();
+
+        for (slot_size_index, count) in equipment.slots().iter().enumerate().rev() {
+            for _ in 0..*count {
+                slot_sizes.push(slot_size_index as i32 + 1);
+            }
+        }
+
+        push_slots(&mut slots, equipment.part().as_str(), &slot_sizes);
+    }
+
+    slots
+}
+
+fn push_slots(slots: &mut Vec<ResultDecorationSlot>, part: &str, slot_sizes: &[i32]) {
+    for (slot_index, slot_size) in slot_sizes.iter().enumerate() {
+        slots.push(ResultDecorationSlot {
+            part: part.to_string(),
+            slot_index,
+            slot_size: *slot_size,
+            deco_id: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        search::search,
+        test::{create_pinned_request, get_data_manager},
+    };
+
+    #[test]
+    fn deco_placement_completes_skills() {
+        let dm = get_data_manager();
+
+        let mut request =
+            create_pinned_request(&[("critical_boost", 3), ("agitator", 3), ("attack_boost", 4)]);
+
+        request.free_slots = vec![1, 0, 0, 0];
+
+        request.limits.max_results = 50;
+
+        let result = search(dm, &request).unwrap();
+
+        assert_ne!(result.full_equipments.len(), 0);
+
+        for equip in &result.full_equipments {
+            assert_ne!(equip.deco_combs.len(), 0);
+
+            let mut equip_skills = equip.talisman.skills.clone();
+
+            for armor in equip.armors.values() {
+                for (id, level) in &armor.skills {
+                    *equip_skills.entry(id.clone()).or_insert(0) += level;
+                }
+            }
+
+            for deco_comb in &equip.deco_combs {
+                let mut skills = equip_skills.clone();
+                let mut empty_slot_count = 0;
+
+                for slot in &deco_comb.placement {
+                    let deco = match &slot.deco_id {
+                        Some(deco_id) => &dm.decos[deco_id],
+                        None => {
+                            empty_slot_count += 1;
+                            continue;
+                        }
+                    };
+
+                    assert!(deco.slot_size <= slot.slot_size);
+
+                    *skills.entry(deco.skill_id.clone()).or_insert(0) += deco.skill_level;
+                }
+
+                assert!(1 <= empty_slot_count);
+
+                for (id, level) in &request.selected_skills {
+                    assert!(level <= skills.get(id).unwrap_or(&0));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::armor::{ArmorPart, ArmorStat};
+use crate::data::data_manager::DataManager;
+use crate::deco_placement::{ResultDecorationSlot, WEAPON_PART};
+use crate::search::{ResultDecorationCombination, ResultFullEquipments, SearchResult};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Full result structure, same as returned to the app
+    #[serde(rename = "json")]
+    Json,
+    /// One row per set
+    #[serde(rename = "csv")]
+    Csv,
+    /// One table per set, for sharing in chats
+    #[serde(rename = "markdown")]
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Format by the file extension (`json`, `csv`, `md` or `markdown`)
+    pub fn from_filename(filename: &Path) -> Option<Self> {
+        let extension = filename.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+const CSV_HEADER: [&str; 16] = [
+    "set",
+    "helm",
+    "torso",
+    "arm",
+    "waist",
+    "feet",
+    "talisman",
+    "defense",
+    "fireRes",
+    "waterRes",
+    "iceRes",
+    "elecRes",
+    "dragonRes",
+    "skills",
+    "decorations",
+    "missingDecorations",
+];
+
+pub fn export_result(
+    dm: &DataManager,
+    result: &SearchResult,
+    lang: &str,
+    format: ExportFormat,
+) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(result)
+            .map_err(|err| format!("Failed to serialize result: {}", err)),
+        ExportFormat::Csv => export_csv(dm, result, lang),
+        ExportFormat::Markdown => Ok(export_markdown(dm, result, lang)),
+    }
+}
+
+pub fn export_result_to_file(
+    dm: &DataManager,
+    result: &SearchResult,
+    lang: &str,
+    format: ExportFormat,
+    filename: &Path,
+) -> Result<(), String> {
+    let text = export_result(dm, result, lang, format)?;
+
+    fs::write(filename, text).map_err(|err| format!("Failed to write {:?}: {}", filename, err))
+}
+
+pub fn skill_name(dm: &DataManager, skill_id: &String, lang: &str) -> String {
+    match dm
+        .skills
+        .get(skill_id)
+        .and_then(|skill| skill.names.get(lang))
+    {
+        Some(name) => name.clone(),
+        None => skill_id.clone(),
+    }
+}
+
+pub fn deco_name(dm: &DataManager, deco_id: &String, lang: &str) -> String {
+    match dm.decos.get(deco_id).and_then(|deco| deco.names.get(lang)) {
+        Some(name) => name.clone(),
+        None => deco_id.clone(),
+    }
+}
+
+/// Name of the base armor, as anomaly armors have no names of their own
+pub fn armor_name(dm: &DataManager, base_id: &String, lang: &str) -> String {
+    match dm.armors.get(base_id).and_then(|base| base.names.get(lang)) {
+        Some(name) => name.clone(),
+        None => base_id.clone(),
+    }
+}
+
+/// Skills of the armors, talisman and placed decorations, capped to max levels
+pub fn get_total_skills(
+    dm: &DataManager,
+    full_equip: &ResultFullEquipments,
+    deco_comb: Option<&ResultDecorationCombination>,
+) -> HashMap<String, i32> {
+    let mut total_skills = HashMap::<String, i32>::new();
+
+    for armor in full_equip.armors.values() {
+        for (id, level) in &armor.skills {
+            *total_skills.entry(id.clone()).or_insert(0) += level;
+        }
+    }
+
+    for (id, level) in &full_equip.talisman.skills {
+        *total_skills.entry(id.clone()).or_insert(0) += level;
+    }
+
+    if let Some(deco_comb) = deco_comb {
+        for slot in &deco_comb.placement {
+            let deco = match slot.deco_id.as_ref().and_then(|id| dm.decos.get(id)) {
+                Some(deco) => deco,
+                None => continue,
+            };
+
+            *total_skills.entry(deco.skill_id.clone()).or_insert(0) += deco.skill_level;
+        }
+    }
+
+    for (id, level) in total_skills.iter_mut() {
+        if let Some(skill) = dm.skills.get(id) {
+            *level = (*level).min(skill.max_level);
+        }
+    }
+
+    total_skills
+}
+
+fn format_skills(dm: &DataManager, skills: &HashMap<String, i32>, lang: &str) -> String {
+    let mut skills = skills
+        .iter()
+        .map(|(id, level)| format!("{} {}", skill_name(dm, id, lang), level))
+        .collect::<Vec<String>>();
+
+    skills.sort();
+
+    skills.join(", ")
+}
+
+fn format_armor(
+    dm: &DataManager,
+    full_equip: &ResultFullEquipments,
+    part: &ArmorPart,
+    lang: &str,
+) -> String {
+    let armor = match full_equip.armors.get(part.as_str()) {
+        Some(armor) => armor,
+        None => return String::new(),
+    };
+
+    let mut name = armor_name(dm, &armor.base_id, lang);
+
+    if armor.is_anomaly {
+        name.push_str(" (anomaly)");
+    }
+
+    // Every armor needs crafting without an inventory
+    if armor.needs_crafting && !dm.armor_inventory.armors.is_empty() {
+        name.push_str(" (craft)");
+    }
+
+    name
+}
+
+fn format_talisman(dm: &DataManager, full_equip: &ResultFullEquipments, lang: &str) -> String {
+    let talisman = &full_equip.talisman;
+
+    let skills = format_skills(dm, &talisman.skills, lang);
+    let slot_sizes = format_slot_sizes(&talisman.slots);
+
+    match (skills.is_empty(), slot_sizes.is_empty()) {
+        (true, true) => String::new(),
+        (false, true) => skills,
+        (true, false) => format!("[{}]", slot_sizes),
+        (false, false) => format!("{} [{}]", skills, slot_sizes),
+    }
+}
+
+/// Slot counts per size as slot sizes, larger first (e.g. `[1, 0, 1, 0]` as "3-1")
+fn format_slot_sizes(slots: &[i32]) -> String {
+    let mut sizes = Vec::new();
+
+    for (index, count) in slots.iter().enumerate().rev() {
+        for _ in 0..*count {
+            sizes.push((index + 1).to_string());
+        }
+    }
+
+    sizes.join("-")
+}
+
+/// Decorations of the placement with their counts
+fn format_decos(dm: &DataManager, placement: &Vec<ResultDecorationSlot>, lang: &str) -> String {
+    let mut counts = HashMap::<&String, i32>::new();
+
+    for slot in placement {
+        if let Some(deco_id) = &slot.deco_id {
+            *counts.entry(deco_id).or_insert(0) += 1;
+        }
+    }
+
+    format_deco_counts(dm, &counts, lang)
+}
+
+fn format_deco_counts(dm: &DataManager, counts: &HashMap<&String, i32>, lang: &str) -> String {
+    let mut decos = counts
+        .iter()
+        .map(|(id, count)| format!("{} x{}", deco_name(dm, id, lang), count))
+        .collect::<Vec<String>>();
+
+    decos.sort();
+
+    decos.join(", ")
+}
+
+/// Slots of a part with the decoration in each, "-" for empty ones
+fn format_part_slots(
+    dm: &DataManager,
+    placement: &[ResultDecorationSlot],
+    part: &str,
+    lang: &str,
+) -> String {
+    placement
+        .iter()
+        .filter(|slot| slot.part == part)
+        .map(|slot| {
+            let deco = match &slot.deco_id {
+                Some(deco_id) => deco_name(dm, deco_id, lang),
+                None => "-".to_string(),
+            };
+
+            format!("[{}] {}", slot.slot_size, deco)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn export_csv(dm: &DataManager, result: &SearchResult, lang: &str) -> Result<String, String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+
+    writer
+        .write_record(CSV_HEADER)
+        .map_err(|err| format!("Failed to write csv: {}", err))?;
+
+    for (index, full_equip) in result.full_equipments.iter().enumerate() {
+        // Sets may be completed in several ways, the first one is exported
+        let deco_comb = full_equip.deco_combs.first();
+
+        let mut record = vec![(index + 1).to_string()];
+
+        for part in ArmorPart::get_all() {
+            record.push(format_armor(dm, full_equip, &part, lang));
+        }
+
+        record.push(format_talisman(dm, full_equip, lang));
+        record.extend(stat_values(&full_equip.stat));
+        record.push(format_skills(
+            dm,
+            &get_total_skills(dm, full_equip, deco_comb),
+            lang,
+        ));
+
+        match deco_comb {
+            Some(deco_comb) => {
+                let missing = deco_comb
+                    .missing_decos
+                    .iter()
+                    .map(|(id, count)| (id, *count))
+                    .collect();
+
+                record.push(format_decos(dm, &deco_comb.placement, lang));
+                record.push(format_deco_counts(dm, &missing, lang));
+            }
+            None => {
+                record.push(String::new());
+                record.push(String::new());
+            }
+        }
+
+        writer
+            .write_record(&record)
+            .map_err(|err| format!("Failed to write csv: {}", err))?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|err| format!("Failed to write csv: {}", err))?;
+
+    String::from_utf8(bytes).map_err(|err| format!("Failed to write csv: {}", err))
+}
+
+fn stat_values(stat: &ArmorStat) -> Vec<String> {
+    vec![
+        stat.defense.to_string(),
+        stat.fire_res.to_string(),
+        stat.water_res.to_string(),
+        stat.ice_res.to_string(),
+        stat.elec_res.to_string(),
+        stat.dragon_res.to_string(),
+    ]
+}
+
+/// Table cells can't have pipes or line breaks
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn export_markdown(dm: &DataManager, result: &SearchResult, lang: &str) -> String {
+    let mut lines = Vec::<String>::new();
+
+    for (index, full_equip) in result.full_equipments.iter().enumerate() {
+        let deco_comb = full_equip.deco_combs.first();
+        let placement = match deco_comb {
+            Some(deco_comb) => deco_comb.placement.clone(),
+            None => Vec::new(),
+        };
+
+        lines.push(format!("## Set {}", index + 1));
+        lines.push(String::new());
+        lines.push("| Part | Equipment | Decorations |".to_string());
+        lines.push("| --- | --- | --- |".to_string());
+
+        let weapon_slots = format_part_slots(dm, &placement, WEAPON_PART, lang);
+
+        if !weapon_slots.is_empty() {
+            lines.push(format!(
+                "| {} | | {} |",
+                WEAPON_PART,
+                escape_markdown(&weapon_slots)
+            ));
+        }
+
+        for part in ArmorPart::get_all() {
+            lines.push(format!(
+                "| {} | {} | {} |",
+                part.as_str(),
+                escape_markdown(&format_armor(dm, full_equip, &part, lang)),
+                escape_markdown(&format_part_slots(dm, &placement, part.as_str(), lang))
+            ));
+        }
+
+        lines.push(format!(
+            "| {} | {} | {} |",
+            ArmorPart::Talisman.as_str(),
+            escape_markdown(&format_talisman(dm, full_equip, lang)),
+            escape_markdown(&format_part_slots(
+                dm,
+                &placement,
+                ArmorPart::Talisman.as_str(),
+                lang
+            ))
+        ));
+
+        lines.push(String::new());
+        lines.push(format!(
+            "**Skills**: {}",
+            format_skills(dm, &get_total_skills(dm, full_equip, deco_comb), lang)
+        ));
+
+        let stat = &full_equip.stat;
+
+        lines.push(String::new());
+        lines.push(format!(
+            "**Defense**: {} / fire {} / water {} / ice {} / elec {} / dragon {}",
+            stat.defense,
+            stat.fire_res,
+            stat.water_res,
+            stat.ice_res,
+            stat.elec_res,
+            stat.dragon_res
+        ));
+
+        if let Some(deco_comb) = deco_comb {
+            if !deco_comb.missing_decos.is_empty() {
+                let missing = deco_comb
+                    .missing_decos
+                    .iter()
+                    .map(|(id, count)| (id, *count))
+                    .collect();
+
+                lines.push(String::new());
+                lines.push(format!(
+                    "**Missing decorations**: {}",
+                    format_deco_counts(dm, &missing, lang)
+                ));
+            }
+        }
+
+        if 1 < full_equip.deco_combs.len() {
+            lines.push(String::new());
+            lines.push(format!(
+                "{} other decoration combination(s)",
+                full_equip.deco_combs.len() - 1
+            ));
+        }
+
+        lines.push(String::new());
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{export_result, ExportFormat};
+    use crate::{
+        search::{search, SearchResult},
+        test::{create_pinned_request, get_data_manager},
+    };
+
+    #[test]
+    fn export_result_formats() {
+        let dm = get_data_manager();
+
+        let mut request = create_pinned_request(&[
+            ("critical_boost", 3),
+            ("agitator", 3),
+            ("attack_boost", 4),
+            ("weakness_exploit", 2),
+        ]);
+
+        request.limits.max_results = 3;
+
+        let result = search(dm, &request).unwrap();
+        let set_count = result.full_equipments.len();
+
+        assert_ne!(set_count, 0);
+
+        let json = export_result(dm, &result, "en", ExportFormat::Json).unwrap();
+        let parsed = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+
+        assert_eq!(
+            parsed["full_equipments"].as_array().unwrap().len(),
+            set_count
+        );
+
+        let csv = export_result(dm, &result, "en", ExportFormat::Csv).unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+
+        assert_eq!(reader.records().count(), set_count);
+        assert!(csv.contains("Silver Solhelm"));
+        assert!(csv.contains("Weakness Exploit 2"));
+
+        let markdown = export_result(dm, &result, "ja", ExportFormat::Markdown).unwrap();
+        let helm_name = &dm.armors["silver_solhelm"].names["ja"];
+
+        assert_eq!(markdown.matches("## Set ").count(), set_count);
+        assert_eq!(markdown.matches(helm_name.as_str()).count(), set_count);
+
+        // The app sends back the result it received to export it
+        let received =
+            serde_json::from_str::<SearchResult>(&serde_json::to_string(&result).unwrap()).unwrap();
+
+        let received_json = export_result(dm, &received, "en", ExportFormat::Json).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&received_json).unwrap(),
+            parsed
+        );
+
+        for format in [ExportFormat::Csv, ExportFormat::Markdown] {
+            assert_eq!(
+                export_result(dm, &received, "ja", format).unwrap(),
+                export_result(dm, &result, "ja", format).unwrap()
+            );
+        }
+    }
+}
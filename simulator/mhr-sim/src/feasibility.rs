@@ -0,0 +1,359 @@
+use std::collections::{HashMap, HashSet};
+
+use itertools::izip;
+
+use crate::calc::armor::CalcArmor;
+use crate::calc::calc_equipment::CalcEquipment;
+use crate::calc::talisman::CalcTalisman;
+use crate::data::armor::{ArmorPart, ArmorSkill, BaseArmor};
+use crate::data::data_manager::DataManager;
+use crate::data::deco_combination::DecorationCombination;
+use crate::data::skill::MAX_SLOT_LEVEL;
+use crate::search::{check_static_conditions, has_possible_deco_combs, SearchRequest};
+use crate::BoxCalcEquipment;
+
+/// Skill levels (capped to the requirement) and slots of some parts combined
+#[derive(Clone, Debug)]
+struct PartialEquipments {
+    levels: Vec<i32>,
+    slots: Vec<i32>,
+}
+
+impl PartialEquipments {
+    /// At least the levels of `other`, with room for its slots
+    fn dominates(&self, other: &PartialEquipments) -> bool {
+        self.levels
+            .iter()
+            .zip(other.levels.iter())
+            .all(|(level, other_level)| other_level <= level)
+            && DecorationCombination::is_possible_static(&self.slots, &other.slots)
+    }
+}
+
+/// Highest level reachable per skill along with the selected skills of the request.
+///
+/// Armors are reduced to their levels of the selected skills (and the queried one) and slots,
+/// then combined part by part keeping only non-dominated combinations.
+/// Each combination left is checked with the same static conditions as search candidates.
+/// `None` if the selected skills themselves are impossible.
+pub fn get_max_skill_levels(
+    dm: &DataManager,
+    request: &SearchRequest,
+) -> Result<Option<HashMap<String, i32>>, String> {
+    request.validate(dm)?;
+
+    let selected_skills = &request.selected_skills;
+
+    let selected_ids = selected_skills.keys().cloned().collect::<Vec<String>>();
+    let selected_caps = selected_ids
+        .iter()
+        .map(|id| selected_skills[id])
+        .collect::<Vec<i32>>();
+
+    // Allowed equipments and the skills they give are the same for every queried skill
+    let part_equipments = get_part_equipments(dm, request);
+
+    let equipment_skill_ids = part_equipments
+        .iter()
+        .flatten()
+        .flat_map(|equipment| equipment.skills().keys().cloned())
+        .collect::<HashSet<String>>();
+
+    let selected_combs = get_combinations(&part_equipments, &selected_ids, &selected_caps);
+
+    let selected_possible = selected_combs
+        .iter()
+        .any(|comb| is_possible(dm, request, &selected_ids, comb, selected_skills));
+
+    if !selected_possible {
+        return Ok(None);
+    }
+
+    let mut max_levels = HashMap::new();
+
+    for (id, skill) in &dm.skills {
+        let selected_level = *selected_skills.get(id).unwrap_or(&0);
+
+        if skill.max_level <= selected_level {
+            max_levels.insert(id.clone(), selected_level);
+            continue;
+        }
+
+        let mut skill_ids = selected_ids.clone();
+        let mut caps = selected_caps.clone();
+
+        match skill_ids.iter().position(|selected_id| selected_id == id) {
+            Some(index) => caps[index] = skill.max_level,
+            None => {
+                skill_ids.push(id.clone());
+                caps.push(skill.max_level);
+            }
+        }
+
+        // Skill not given by any allowed equipment only changes decoration requirements
+        let combs = if selected_level == 0 && !equipment_skill_ids.contains(id) {
+            selected_combs.clone()
+        } else {
+            get_combinations(&part_equipments, &skill_ids, &caps)
+        };
+
+        let mut max_level = selected_level;
+
+        for comb in &combs {
+            while max_level < skill.max_level {
+                let mut req_skills = selected_skills.clone();
+                req_skills.insert(id.clone(), max_level + 1);
+
+                if !is_possible(dm, request, &skill_ids, comb, &req_skills) {
+                    break;
+                }
+
+                max_level += 1;
+            }
+        }
+
+        max_levels.insert(id.clone(), max_level);
+    }
+
+    Ok(Some(max_levels))
+}
+
+/// Allowed equipments of each part, then talismans, with the empty one unless the part is pinned
+fn get_part_equipments<'a>(
+    dm: &'a DataManager,
+    request: &SearchRequest,
+) -> Vec<Vec<BoxCalcEquipment<'a>>> {
+    let mut part_equipments = Vec::new();
+
+    for part in ArmorPart::get_all() {
+        let mut equipments = Vec::<BoxCalcEquipment>::new();
+
+        if !request.constraints.is_pinned(&part) {
+            equipments.push(CalcArmor::new(&dm.empty_armors[&part]).clone_dyn());
+        }
+
+        for armor in dm.get_parts(part) {
+            let original = match dm.get_anomaly_armor(armor.id()) {
+                Some(anomaly) => &anomaly.original,
+                None => armor,
+            };
+
+            if !armor.sex_type.is_wearable_by(&request.sex_type)
+                || !request
+                    .constraints
+                    .allows_armor(armor, original, &dm.armor_inventory)
+            {
+                continue;
+            }
+
+            equipments.push(CalcArmor::new(armor).clone_dyn());
+        }
+
+        part_equipments.push(equipments);
+    }
+
+    let mut talismans = Vec::<BoxCalcEquipment>::new();
+
+    if !request.constraints.is_pinned(&ArmorPart::Talisman) {
+        talismans.push(CalcTalisman::new(&dm.empty_talisman).clone_dyn());
+    }
+
+    for tali in &dm.talismans {
+        if request.constraints.allows_talisman(tali) {
+            talismans.push(CalcTalisman::new(tali).clone_dyn());
+        }
+    }
+
+    part_equipments.push(talismans);
+
+    part_equipments
+}
+
+/// Combinations of all parts, reduced to the levels of `skill_ids` and slots.
+/// Weapon slots are added when checking them.
+fn get_combinations(
+    part_equipments: &[Vec<BoxCalcEquipment>],
+    skill_ids: &[String],
+    caps: &Vec<i32>,
+) -> Vec<PartialEquipments> {
+    let mut combs = vec![PartialEquipments {
+        levels: vec![0; skill_ids.len()],
+        slots: vec![0; MAX_SLOT_LEVEL],
+    }];
+
+    for equipments in part_equipments {
+        combs = combine(&combs, &reduce(equipments, skill_ids, caps), caps);
+    }
+
+    combs
+}
+
+fn reduce(
+    equipments: &Vec<BoxCalcEquipment>,
+    skill_ids: &[String],
+    caps: &[i32],
+) -> Vec<PartialEquipments> {
+    let partials = equipments
+        .iter()
+        .map(|equipment| PartialEquipments {
+            levels: skill_ids
+                .iter()
+                .zip(caps.iter())
+                .map(|(id, cap)| (*equipment.skills().get(id).unwrap_or(&0)).min(*cap))
+                .collect(),
+            slots: equipment.slots().clone(),
+        })
+        .collect();
+
+    remove_dominated(partials)
+}
+
+fn combine(
+    combs: &Vec<PartialEquipments>,
+    part_combs: &Vec<PartialEquipments>,
+    caps: &Vec<i32>,
+) -> Vec<PartialEquipments> {
+    let mut next_combs = Vec::new();
+
+    for comb in combs {
+        for part_comb in part_combs {
+            next_combs.push(PartialEquipments {
+                levels: izip!(&comb.levels, &part_comb.levels, caps)
+                    .map(|(level1, level2, cap)| (level1 + level2).min(*cap))
+                    .collect(),
+                slots: izip!(&comb.slots, &part_comb.slots)
+                    .map(|(count1, count2)| count1 + count2)
+                    .collect(),
+            });
+        }
+    }
+
+    remove_dominated(next_combs)
+}
+
+/// Keeps combinations that no other one dominates, in their order
+fn remove_dominated(combs: Vec<PartialEquipments>) -> Vec<PartialEquipments> {
+    let mut ret = Vec::<PartialEquipments>::new();
+
+    for comb in combs {
+        if ret.iter().any(|other| other.dominates(&comb)) {
+            continue;
+        }
+
+        ret.retain(|other| !comb.dominates(other));
+        ret.push(comb);
+    }
+
+    ret
+}
+
+fn is_possible(
+    dm: &DataManager,
+    request: &SearchRequest,
+    skill_ids: &[String],
+    comb: &PartialEquipments,
+    req_skills: &HashMap<String, i32>,
+) -> bool {
+    // Whole combination as a single armor
+    let mut armor = BaseArmor::create_empty(ArmorPart::Helm);
+
+    for (id, level) in skill_ids.iter().zip(comb.levels.iter()) {
+        armor
+            .skills
+            .insert(id.clone(), ArmorSkill { level: *level });
+    }
+
+    armor.slots = Vec::new();
+
+    for (slot_size_index, count) in comb.slots.iter().enumerate() {
+        for _ in 0..*count {
+            armor.slots.push(slot_size_index as i32 + 1);
+        }
+    }
+
+    let mut equipments = vec![CalcArmor::new(&armor).clone_dyn()];
+
+    for part in ArmorPart::get_all().into_iter().skip(1) {
+        equipments.push(CalcArmor::new(&dm.empty_armors[&part]).clone_dyn());
+    }
+
+    equipments.push(CalcTalisman::new(&dm.empty_talisman).clone_dyn());
+
+    let leftovers = check_static_conditions(
+        dm,
+        &request.weapon_slots,
+        req_skills,
+        &request.free_slots,
+        request.deco_inventory.as_ref(),
+        &equipments.iter().collect::<Vec<_>>(),
+    );
+
+    match leftovers {
+        Some((multi_deco_req_skills, avail_slots)) => has_possible_deco_combs(
+            dm,
+            request.deco_inventory.as_ref(),
+            &multi_deco_req_skills,
+            &avail_slots,
+        ),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_max_skill_levels;
+    use crate::{
+        data::armor::ArmorPart,
+        test::{create_pinned_request, get_data_manager},
+    };
+
+    #[test]
+    fn max_skill_levels_of_pinned_armors() {
+        let dm = get_data_manager();
+
+        let mut request = create_pinned_request(&[("critical_boost", 3)]);
+
+        request.weapon_slots = vec![0, 0, 0];
+        request
+            .constraints
+            .pinned_armors
+            .insert(ArmorPart::Waist, "silver_solcoil".to_string());
+        request
+            .constraints
+            .pinned_armors
+            .insert(ArmorPart::Feet, "silver_solgreaves".to_string());
+
+        let max_levels = get_max_skill_levels(dm, &request).unwrap().unwrap();
+
+        // Skills without decorations are limited to the pinned armors
+        assert_eq!(max_levels["sneak_attack"], 1);
+        assert_eq!(max_levels["element_exploit"], 2);
+        assert_eq!(max_levels["critical_boost"], 3);
+
+        request
+            .selected_skills
+            .insert("sneak_attack".to_string(), 2);
+
+        assert!(get_max_skill_levels(dm, &request).unwrap().is_none());
+
+        // Weapon slots take decorations too
+        request.selected_skills.remove("sneak_attack");
+
+        let without_weapon_slots = get_max_skill_levels(dm, &request).unwrap().unwrap();
+
+        request.weapon_slots = vec![4, 4, 4];
+
+        let with_weapon_slots = get_max_skill_levels(dm, &request).unwrap().unwrap();
+
+        assert!(without_weapon_slots["attack_boost"] < with_weapon_slots["attack_boost"]);
+
+        // Other armors only add choices
+        request.constraints.pinned_armors.clear();
+
+        let unpinned = get_max_skill_levels(dm, &request).unwrap().unwrap();
+
+        assert!(with_weapon_slots
+            .iter()
+            .all(|(id, level)| *level <= unpinned[id]));
+    }
+}
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
 use crate::{
+    calc::{armor::CalcArmor, calc_equipment::CalcEquipment, talisman::CalcTalisman},
     data::{
         armor::ArmorPart,
+        data_manager::DataManager,
         deco_combination::{DecorationCombination, DecorationCombinations},
         skill::MAX_SLOT_LEVEL,
     },
+    search::ResultFullEquipments,
     BoxCalcEquipment,
 };
 
@@ -21,7 +24,10 @@ pub struct FullEquipments<'a> {
 }
 
 impl<'a> FullEquipments<'a> {
-    pub fn new(weapon_slots: Vec<i32>, equipments: Vec<BoxCalcEquipment<'a>>) -> FullEquipments {
+    pub fn new(
+        weapon_slots: Vec<i32>,
+        equipments: Vec<BoxCalcEquipment<'a>>,
+    ) -> FullEquipments<'a> {
         let equipments_by_part = Self::save_by_part_clone(&equipments);
         let (all_skills, avail_slots) = Self::calculate_skills_slots(&weapon_slots, &equipments);
 
@@ -34,6 +40,46 @@ impl<'a> FullEquipments<'a> {
         }
     }
 
+    /// Rebuilds the equipments of a search result, which may refer to loaded anomalies and talismans
+    pub fn from_result(
+        dm: &'a DataManager,
+        weapon_slots: Vec<i32>,
+        result: &ResultFullEquipments,
+    ) -> Result<FullEquipments<'a>, String> {
+        let mut equipments = Vec::<BoxCalcEquipment<'a>>::new();
+
+        for part in ArmorPart::get_all() {
+            let armor_id = match result.armors.get(part.as_str()) {
+                Some(result_armor) => result_armor.id.clone(),
+                None => dm.empty_armors[&part].id().clone(),
+            };
+
+            let calc_armor = match dm.get_armor_by_id(&armor_id) {
+                Some((armor, original)) => CalcArmor::<'a>::new_anomaly(armor, original),
+                None => return Err(format!("Unknown armor: {}", armor_id)),
+            };
+
+            if calc_armor.part() != &part {
+                return Err(format!(
+                    "Armor {} is not a {} armor",
+                    armor_id,
+                    part.as_str()
+                ));
+            }
+
+            equipments.push(calc_armor.clone_dyn());
+        }
+
+        let tali_id = &result.talisman.id;
+
+        match dm.get_talisman_by_id(tali_id) {
+            Some(tali) => equipments.push(CalcTalisman::<'a>::new(tali).clone_dyn()),
+            None => return Err(format!("Unknown talisman: {}", tali_id)),
+        }
+
+        Ok(Self::new(weapon_slots, equipments))
+    }
+
     pub fn get_by_part(&self, part: &ArmorPart) -> &BoxCalcEquipment<'a> {
         &self.equipments_by_part[part]
     }
@@ -41,16 +87,18 @@ impl<'a> FullEquipments<'a> {
     pub fn get_possible_combs(
         &self,
         mut req_skills: HashMap<String, i32>,
-        req_slots: &Vec<i32>,
+        req_slots: &[i32],
         no_deco_skills: &HashMap<String, i32>,
         deco_comb_calculator: &DecorationCombinations,
     ) -> (bool, Vec<DecorationCombination>) {
         let mut avail_slots = self.avail_slots.clone();
 
-        let slot_available =
-            DecorationCombination::is_possible_static_mut(&mut avail_slots, &mut req_slots.clone());
+        let slot_available = DecorationCombination::is_possible_static_mut(
+            &mut avail_slots,
+            &mut req_slots.to_vec(),
+        );
 
-        if slot_available == false {
+        if !slot_available {
             return (false, Vec::new());
         }
 
@@ -59,10 +107,8 @@ impl<'a> FullEquipments<'a> {
         for (id, level) in req_skills.clone() {
             let existing = self.all_skills.get(&id);
 
-            if existing.is_some() {
-                if level - existing.unwrap() <= 0 {
-                    remove_ids.push(id);
-                }
+            if existing.is_some() && level - existing.unwrap() <= 0 {
+                remove_ids.push(id);
             }
         }
 
@@ -70,7 +116,7 @@ impl<'a> FullEquipments<'a> {
             req_skills.remove(&id);
         }
 
-        if req_skills.len() == 0 {
+        if req_skills.is_empty() {
             return (
                 true,
                 vec![DecorationCombination {
@@ -80,7 +126,7 @@ impl<'a> FullEquipments<'a> {
             );
         }
 
-        for (id, _) in &req_skills {
+        for id in req_skills.keys() {
             if no_deco_skills.contains_key(id) {
                 return (false, Vec::new());
             }
@@ -89,17 +135,15 @@ impl<'a> FullEquipments<'a> {
         let mut req_deco_combs = deco_comb_calculator.get_possible_combs(&req_skills);
         req_deco_combs.retain(|comb| comb.is_possible(&avail_slots));
 
-        (req_deco_combs.len() != 0, req_deco_combs)
+        (!req_deco_combs.is_empty(), req_deco_combs)
     }
 
     pub fn contains_skills(&self, req_skills: &HashMap<String, i32>) -> bool {
         for (id, req_level) in req_skills.clone() {
             let existing = self.all_skills.get(&id);
 
-            if existing.is_some() {
-                if req_level - existing.unwrap() <= 0 {
-                    continue;
-                }
+            if existing.is_some() && req_level - existing.unwrap() <= 0 {
+                continue;
             }
 
             return false;
@@ -113,23 +157,11 @@ impl<'a> FullEquipments<'a> {
         equipments: &Vec<BoxCalcEquipment<'a>>,
     ) -> (HashMap<String, i32>, Vec<i32>) {
         let mut skills = HashMap::<String, i32>::new();
-        let mut slots = Vec::<i32>::new();
-
-        for _ in 0..MAX_SLOT_LEVEL {
-            slots.push(0);
-        }
+        let mut slots = vec![0; MAX_SLOT_LEVEL];
 
         for equip in equipments {
             for (id, &level) in equip.skills() {
-                let existing = skills.get(id);
-
-                let mut level_sum = level;
-
-                if existing.is_some() {
-                    level_sum += existing.unwrap();
-                }
-
-                skills.insert(id.clone(), level_sum);
+                *skills.entry(id.clone()).or_insert(0) += level;
             }
 
             for (slot_size_index, count) in equip.slots().iter().enumerate() {
@@ -149,7 +181,7 @@ impl<'a> FullEquipments<'a> {
             slots[(weapon_slot - 1) as usize] += 1;
         }
 
-        return (skills, slots);
+        (skills, slots)
     }
 
     // TODO: why lifetime error?
@@ -0,0 +1,59 @@
+pub mod data {
+    pub mod armor;
+    pub mod data_manager;
+    pub mod data_override;
+    pub mod data_validation;
+    pub mod deco;
+    pub mod deco_combination;
+    pub mod name_dict;
+    pub mod skill;
+}
+
+pub mod calc {
+    pub mod armor;
+    pub mod calc_equipment;
+    pub mod deco;
+    pub mod talisman;
+}
+
+pub mod additional_skills;
+pub mod armor_inventory;
+pub mod brute_force;
+pub mod build_code;
+pub mod constraints;
+pub mod deco_inventory;
+pub mod deco_placement;
+pub mod export;
+pub mod feasibility;
+pub mod full_equipments;
+pub mod loadout;
+pub mod parse;
+pub mod profile;
+pub mod progress;
+pub mod search;
+
+#[cfg(test)]
+mod test;
+
+use crate::calc::calc_equipment::CalcEquipment;
+
+pub use crate::additional_skills::{
+    search_additional_skills, AdditionalSkill, AdditionalSkillsRequest,
+};
+pub use crate::armor_inventory::{ArmorAvailability, ArmorOwnership, OwnedArmor};
+pub use crate::brute_force::{brute_force_search, BruteForceSet};
+pub use crate::build_code::{decode_build, encode_build, DecodedBuild};
+pub use crate::constraints::SearchConstraints;
+pub use crate::deco_inventory::{DecoInventory, OwnedDeco};
+pub use crate::export::{export_result, export_result_to_file, ExportFormat};
+pub use crate::feasibility::get_max_skill_levels;
+pub use crate::loadout::{CheckedLoadout, LoadoutStore, SavedLoadout};
+pub use crate::parse::{
+    create_data_manager, create_data_manager_with_overrides, load_data, parse_anomaly,
+    parse_armor_inventory, parse_deco_inventory, parse_talisman, CsvImport, CsvImportError,
+};
+pub use crate::profile::HunterProfile;
+pub use crate::progress::{SearchMonitor, SearchPhase, SearchProgress};
+pub use crate::search::{search, search_with_monitor, SearchLimits, SearchRequest, SearchResult};
+
+pub type BoxCalcEquipment<'a> = Box<dyn CalcEquipment<'a> + 'a>;
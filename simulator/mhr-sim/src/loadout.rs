@@ -0,0 +1,255 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::calc::calc_equipment::CalcEquipment;
+use crate::data::armor::{ArmorPart, StatType};
+use crate::data::data_manager::DataManager;
+use crate::full_equipments::FullEquipments;
+use crate::search::ResultFullEquipments;
+
+pub static LOADOUTS_FILENAME: &str = "loadouts.json";
+
+/// A result saved to the equipment box
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedLoadout {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Seconds since the unix epoch
+    #[serde(rename = "savedAt")]
+    pub saved_at: u64,
+
+    #[serde(rename = "weaponSlots")]
+    pub weapon_slots: Vec<i32>,
+
+    /// Equipments with the stats they had when saved, anomaly armors and talismans included
+    pub equipments: ResultFullEquipments,
+}
+
+/// Saved loadout re-validated against the loaded data
+#[derive(Serialize, Debug, Clone)]
+pub struct CheckedLoadout {
+    #[serde(flatten)]
+    pub loadout: SavedLoadout,
+
+    /// Anomaly armors or talismans missing or changed since the loadout was saved
+    pub broken: bool,
+    pub problems: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LoadoutStore {
+    #[serde(rename = "nextId")]
+    next_id: u32,
+    pub loadouts: Vec<SavedLoadout>,
+}
+
+impl LoadoutStore {
+    /// Missing file is an empty store, unlike an unreadable one which would be lost on save
+    pub fn load(filename: &Path) -> Result<Self, String> {
+        let file = match File::open(filename) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(format!("Failed to open {:?}: {}", filename, err)),
+        };
+
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| format!("Invalid loadouts {:?}: {}", filename, err))
+    }
+
+    pub fn save(&self, filename: &Path) -> io::Result<()> {
+        if let Some(dir) = filename.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = File::create(filename)?;
+
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    pub fn add(
+        &mut self,
+        name: String,
+        notes: String,
+        tags: Vec<String>,
+        weapon_slots: Vec<i32>,
+        equipments: ResultFullEquipments,
+    ) -> &SavedLoadout {
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        self.next_id += 1;
+
+        self.loadouts.push(SavedLoadout {
+            id: self.next_id,
+            name,
+            notes,
+            tags,
+            saved_at,
+            weapon_slots,
+            equipments,
+        });
+
+        self.loadouts.last().unwrap()
+    }
+
+    /// Whether a loadout with the id existed
+    pub fn delete(&mut self, id: u32) -> bool {
+        let count = self.loadouts.len();
+
+        self.loadouts.retain(|loadout| loadout.id != id);
+
+        self.loadouts.len() != count
+    }
+
+    pub fn get_checked(&self, dm: &DataManager) -> Vec<CheckedLoadout> {
+        self.loadouts
+            .iter()
+            .map(|loadout| {
+                let problems = check_loadout(dm, loadout);
+
+                CheckedLoadout {
+                    loadout: loadout.clone(),
+                    broken: !problems.is_empty(),
+                    problems,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Anomaly armors and talismans are referred to by their index in the loaded files,
+/// so the same id may have other stats after a re-import
+pub fn check_loadout(dm: &DataManager, loadout: &SavedLoadout) -> Vec<String> {
+    let full_equipments =
+        match FullEquipments::from_result(dm, loadout.weapon_slots.clone(), &loadout.equipments) {
+            Ok(full_equipments) => full_equipments,
+            Err(message) => return vec![message],
+        };
+
+    let mut problems = Vec::new();
+
+    for part in ArmorPart::get_all() {
+        let saved = match loadout.equipments.armors.get(part.as_str()) {
+            Some(saved) => saved,
+            None => continue,
+        };
+
+        let armor = full_equipments.get_by_part(&part).as_armor();
+
+        let stat_changed = StatType::get_all()
+            .iter()
+            .any(|stat_type| armor.stat().get(stat_type) != saved.stat.get(stat_type));
+
+        if armor.original_id() != &saved.base_id
+            || armor.skills() != &saved.skills
+            || armor.slots() != &saved.slots
+            || stat_changed
+        {
+            problems.push(format!("Armor {} changed since saved", saved.id));
+        }
+    }
+
+    let saved_tali = &loadout.equipments.talisman;
+    let talisman = full_equipments.get_by_part(&ArmorPart::Talisman);
+
+    if talisman.skills() != &saved_tali.skills || talisman.slots() != &saved_tali.slots {
+        problems.push(format!("Talisman {} changed since saved", saved_tali.id));
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::LoadoutStore;
+    use crate::{
+        data::armor::{AnomalyArmor, ArmorPart, ArmorStat},
+        search::search,
+        test::{create_pinned_request, get_data_manager},
+    };
+
+    #[test]
+    fn saved_loadouts_detect_reimported_anomalies() {
+        let mut dm = get_data_manager().clone();
+
+        let helm = dm.armors["silver_solhelm"].clone();
+        let create_anomaly = |defense: i32| {
+            AnomalyArmor::new(
+                helm.clone(),
+                ArmorStat {
+                    defense,
+                    ..Default::default()
+                },
+                vec![0, 0, 0],
+                HashMap::new(),
+            )
+        };
+
+        dm.set_anomalies(vec![create_anomaly(10)]);
+
+        let mut request = create_pinned_request(&[("critical_boost", 3), ("agitator", 3)]);
+
+        request
+            .constraints
+            .pinned_armors
+            .insert(ArmorPart::Helm, "__anomaly_0_silver_solhelm".to_string());
+
+        request.limits.max_results = 1;
+
+        let result = search(&dm, &request).unwrap();
+
+        assert_eq!(result.full_equipments.len(), 1);
+
+        let filename = std::env::temp_dir().join("mhr_sim_test_loadouts.json");
+
+        let mut store = LoadoutStore::default();
+        let id = store
+            .add(
+                "agitator".to_string(),
+                "for rampage".to_string(),
+                vec!["crit".to_string()],
+                request.weapon_slots.clone(),
+                result.full_equipments[0].clone(),
+            )
+            .id;
+        store.save(&filename).unwrap();
+
+        let mut store = LoadoutStore::load(&filename).unwrap();
+
+        std::fs::remove_file(&filename).unwrap();
+
+        assert_eq!(store.loadouts.len(), 1);
+        assert_eq!(store.loadouts[0].tags, vec!["crit".to_string()]);
+        assert!(!store.get_checked(&dm)[0].broken);
+
+        // Same index, other stats
+        dm.set_anomalies(vec![create_anomaly(20)]);
+
+        let checked = store.get_checked(&dm);
+        assert!(checked[0].broken);
+        assert_eq!(checked[0].problems.len(), 1);
+
+        dm.set_anomalies(Vec::new());
+        assert!(store.get_checked(&dm)[0].broken);
+
+        assert!(store.delete(id));
+        assert!(!store.delete(id));
+        assert_eq!(store.loadouts.len(), 0);
+        assert!(LoadoutStore::load(&filename).unwrap().loadouts.is_empty());
+    }
+}
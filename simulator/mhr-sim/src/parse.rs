@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+
+use csv::StringRecord;
+use log::debug;
+use serde::{de, Deserialize, Serialize};
+
+use crate::armor_inventory::{ArmorOwnership, OwnedArmor};
+use crate::data::armor::{AnomalyArmor, ArmorSkill, ArmorStat, BaseArmor, Talisman, TalismanSkill};
+use crate::data::data_manager::DataManager;
+use crate::data::data_override::{apply_patches, OverrideFile, OverrideKind};
+use crate::data::data_validation::{validate_data, DataReport, IssueSeverity};
+use crate::data::deco::Decoration;
+use crate::data::name_dict::NameDict;
+use crate::data::skill::Skill;
+use crate::deco_inventory::OwnedDeco;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CsvImportErrorKind {
+    #[serde(rename = "invalidRecord")]
+    InvalidRecord,
+    #[serde(rename = "missingColumn")]
+    MissingColumn,
+    #[serde(rename = "invalidNumber")]
+    InvalidNumber,
+    #[serde(rename = "unknownArmor")]
+    UnknownArmor,
+    #[serde(rename = "unknownSkill")]
+    UnknownSkill,
+    #[serde(rename = "unknownDecoration")]
+    UnknownDecoration,
+    #[serde(rename = "invalidValue")]
+    InvalidValue,
+    /// Name of several armors, skills or decorations, needs the csv language
+    #[serde(rename = "ambiguousName")]
+    AmbiguousName,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CsvImportError {
+    /// Line in the file, starting from 1
+    pub line: u64,
+    /// Column in the line, starting from 1
+    pub column: usize,
+    pub kind: CsvImportErrorKind,
+    pub value: String,
+    /// Most similar known name, for unknown armor, skill and decoration names
+    #[serde(rename = "closestName")]
+    pub closest_name: Option<String>,
+}
+
+impl fmt::Display for CsvImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {:?} '{}'",
+            self.line, self.column, self.kind, self.value
+        )?;
+
+        if let Some(closest_name) = &self.closest_name {
+            write!(f, ", did you mean '{}'?", closest_name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Rows imported from a csv file, rows with errors are left out
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CsvImport<T> {
+    pub values: Vec<T>,
+    pub errors: Vec<CsvImportError>,
+}
+
+/// Reads cells of a record, collecting errors instead of failing on the first one
+struct CsvRow<'r> {
+    record: &'r StringRecord,
+    line: u64,
+    /// Language of names in the record, any language if `None`
+    lang: Option<&'r str>,
+    errors: Vec<CsvImportError>,
+}
+
+impl<'r> CsvRow<'r> {
+    fn new(record: &'r StringRecord, lang: Option<&'r str>) -> Self {
+        let line = match record.position() {
+            Some(position) => position.line(),
+            None => 0,
+        };
+
+        Self {
+            record,
+            line,
+            lang,
+            errors: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.record.len()
+    }
+
+    fn add_error(
+        &mut self,
+        index: usize,
+        kind: CsvImportErrorKind,
+        value: &str,
+        closest_name: Option<String>,
+    ) {
+        self.errors.push(CsvImportError {
+            line: self.line,
+            column: index + 1,
+            kind,
+            value: value.to_string(),
+            closest_name,
+        });
+    }
+
+    fn get_cell(&mut self, index: usize) -> Option<&'r str> {
+        let value = self.record.get(index).map(|value| value.trim());
+
+        if value.is_none() {
+            self.add_error(index, CsvImportErrorKind::MissingColumn, "", None);
+        }
+
+        value
+    }
+
+    fn get_str(&mut self, index: usize) -> &'r str {
+        self.get_cell(index).unwrap_or("")
+    }
+
+    fn get_i32(&mut self, index: usize) -> i32 {
+        let value = match self.get_cell(index) {
+            Some(value) => value,
+            None => return 0,
+        };
+
+        match value.parse() {
+            Ok(number) => number,
+            Err(_) => {
+                self.add_error(index, CsvImportErrorKind::InvalidNumber, value, None);
+                0
+            }
+        }
+    }
+
+    fn get_id(
+        &mut self,
+        index: usize,
+        name_dict: &NameDict,
+        kind: CsvImportErrorKind,
+    ) -> Option<String> {
+        let name = self.get_cell(index)?;
+        let ids = name_dict.get_ids(self.lang, name);
+
+        match ids.len() {
+            0 => {
+                let closest_name = get_closest_name(name, &name_dict.get_names(self.lang));
+                self.add_error(index, kind, name, closest_name);
+                None
+            }
+            1 => Some(ids[0].clone()),
+            _ => {
+                self.add_error(index, CsvImportErrorKind::AmbiguousName, name, None);
+                None
+            }
+        }
+    }
+}
+
+fn get_closest_name(name: &str, known_names: &Vec<&String>) -> Option<String> {
+    let mut closest: Option<(f64, &String)> = None;
+
+    for &known_name in known_names {
+        let similarity = strsim::normalized_levenshtein(name, known_name);
+
+        let is_closer = match closest {
+            Some((closest_similarity, closest_name)) => {
+                closest_similarity < similarity
+                    || (closest_similarity == similarity && known_name < closest_name)
+            }
+            None => true,
+        };
+
+        if is_closer {
+            closest = Some((similarity, known_name));
+        }
+    }
+
+    closest.map(|(_, closest_name)| closest_name.clone())
+}
+
+/// Records of a headerless csv file, unreadable lines are returned as errors
+fn read_csv_records(filename: &str) -> Result<(Vec<StringRecord>, Vec<CsvImportError>), String> {
+    let file =
+        File::open(filename).map_err(|err| format!("Failed to open {}: {}", filename, err))?;
+
+    let reader = BufReader::new(file);
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader);
+
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+
+    for result in csv_reader.records() {
+        match result {
+            Ok(record) => records.push(record),
+            Err(err) => {
+                let line = match err.position() {
+                    Some(position) => position.line(),
+                    None => 0,
+                };
+
+                errors.push(CsvImportError {
+                    line,
+                    column: 0,
+                    kind: CsvImportErrorKind::InvalidRecord,
+                    value: err.to_string(),
+                    closest_name: None,
+                });
+            }
+        }
+    }
+
+    Ok((records, errors))
+}
+
+fn parse_data<T>(filename: &str) -> Result<Vec<T>, String>
+where
+    T: de::DeserializeOwned,
+{
+    let file =
+        File::open(filename).map_err(|err| format!("Failed to open {}: {}", filename, err))?;
+
+    let reader = BufReader::new(file);
+
+    serde_json::from_reader(reader)
+        .map_err(|err| format!("Invalid data file {}: {}", filename, err))
+}
+
+/// Names are looked up in `lang` (the game client language of the csv), or in any language.
+/// Fails only if the file can't be read, invalid rows are reported in `errors`
+pub fn parse_anomaly(
+    filename: &str,
+    armors: &HashMap<String, BaseArmor>,
+    armor_name_dict: &NameDict,
+    skill_name_dict: &NameDict,
+    lang: Option<&str>,
+) -> Result<CsvImport<AnomalyArmor>, String> {
+    let (records, mut errors) = read_csv_records(filename)?;
+
+    let mut anomaly_armors = Vec::new();
+
+    for record in &records {
+        let mut row = CsvRow::new(record, lang);
+
+        let armor_id = row.get_id(0, armor_name_dict, CsvImportErrorKind::UnknownArmor);
+
+        let defense = row.get_i32(1);
+
+        let fire_res = row.get_i32(2);
+        let water_res = row.get_i32(3);
+        let elec_res = row.get_i32(4);
+        let ice_res = row.get_i32(5);
+        let dragon_res = row.get_i32(6);
+
+        let slot_size1 = row.get_i32(7);
+        let slot_size2 = row.get_i32(8);
+        let slot_size3 = row.get_i32(9);
+
+        let slot_sizes = vec![slot_size1, slot_size2, slot_size3];
+
+        let stat = ArmorStat {
+            defense,
+            fire_res,
+            water_res,
+            elec_res,
+            ice_res,
+            dragon_res,
+        };
+
+        let mut anomaly_skills = HashMap::new();
+
+        for i in (10..row.len()).step_by(2) {
+            if row.get_str(i).is_empty() {
+                continue;
+            }
+
+            let skill_id = row.get_id(i, skill_name_dict, CsvImportErrorKind::UnknownSkill);
+            let skill_level = row.get_i32(i + 1);
+
+            if let Some(skill_id) = skill_id {
+                anomaly_skills.insert(skill_id, ArmorSkill { level: skill_level });
+            }
+        }
+
+        let armor_info = armor_id.as_ref().and_then(|armor_id| armors.get(armor_id));
+
+        if armor_id.is_some() && armor_info.is_none() {
+            let armor_name = row.get_str(0);
+            row.add_error(0, CsvImportErrorKind::UnknownArmor, armor_name, None);
+        }
+
+        if !row.errors.is_empty() {
+            errors.append(&mut row.errors);
+            continue;
+        }
+
+        let anomaly_armor = AnomalyArmor::new(
+            armor_info.unwrap().clone(),
+            stat,
+            slot_sizes,
+            anomaly_skills,
+        );
+
+        anomaly_armors.push(anomaly_armor);
+    }
+
+    debug!(
+        "Anomaly parsed - count : {}, errors: {}",
+        anomaly_armors.len(),
+        errors.len()
+    );
+
+    Ok(CsvImport {
+        values: anomaly_armors,
+        errors,
+    })
+}
+
+/// Fails only if the file can't be read, invalid rows are reported in `errors`
+pub fn parse_talisman(
+    filename: &str,
+    skill_name_dict: &NameDict,
+    lang: Option<&str>,
+) -> Result<CsvImport<Talisman>, String> {
+    let (records, mut errors) = read_csv_records(filename)?;
+
+    let mut talismans = Vec::new();
+
+    for (index, record) in records.iter().enumerate() {
+        let mut row = CsvRow::new(record, lang);
+
+        let mut talisman_skills = Vec::new();
+
+        for (name_index, level_index) in [(0, 1), (2, 3)] {
+            if row.get_str(name_index).is_empty() {
+                continue;
+            }
+
+            let skill_id = row.get_id(
+                name_index,
+                skill_name_dict,
+                CsvImportErrorKind::UnknownSkill,
+            );
+            let skill_level = row.get_i32(level_index);
+
+            if let Some(skill_id) = skill_id {
+                talisman_skills.push(TalismanSkill {
+                    id: skill_id,
+                    level: skill_level,
+                });
+            }
+        }
+
+        let slot_size1 = row.get_i32(4);
+        let slot_size2 = row.get_i32(5);
+        let slot_size3 = row.get_i32(6);
+
+        let slot_sizes = vec![slot_size1, slot_size2, slot_size3];
+
+        if !row.errors.is_empty() {
+            errors.append(&mut row.errors);
+            continue;
+        }
+
+        // Ids follow the line order, `DataManager::set_talismans` numbers them again
+        let talisman = Talisman::new(format!("talisman_{}", index), talisman_skills, slot_sizes);
+
+        talismans.push(talisman);
+    }
+
+    debug!(
+        "Talisman parsed - count : {}, errors: {}",
+        talismans.len(),
+        errors.len()
+    );
+
+    Ok(CsvImport {
+        values: talismans,
+        errors,
+    })
+}
+
+/// Lines of armor name and optionally `owned` (default) or `craftable`.
+/// Fails only if the file can't be read, invalid rows are reported in `errors`
+pub fn parse_armor_inventory(
+    filename: &str,
+    armor_name_dict: &NameDict,
+    lang: Option<&str>,
+) -> Result<CsvImport<OwnedArmor>, String> {
+    let (records, mut errors) = read_csv_records(filename)?;
+
+    let mut owned_armors = Vec::new();
+
+    for record in &records {
+        let mut row = CsvRow::new(record, lang);
+
+        let armor_id = row.get_id(0, armor_name_dict, CsvImportErrorKind::UnknownArmor);
+
+        let ownership = if row.len() < 2 {
+            ArmorOwnership::Owned
+        } else {
+            match row.get_str(1).to_lowercase().as_str() {
+                "" | "owned" => ArmorOwnership::Owned,
+                "craftable" => ArmorOwnership::Craftable,
+                _ => {
+                    let value = row.get_str(1);
+                    row.add_error(1, CsvImportErrorKind::InvalidValue, value, None);
+                    ArmorOwnership::Owned
+                }
+            }
+        };
+
+        if !row.errors.is_empty() {
+            errors.append(&mut row.errors);
+            continue;
+        }
+
+        owned_armors.push(OwnedArmor {
+            id: armor_id.unwrap(),
+            ownership,
+        });
+    }
+
+    debug!(
+        "Armor inventory parsed - count : {}, errors: {}",
+        owned_armors.len(),
+        errors.len()
+    );
+
+    Ok(CsvImport {
+        values: owned_armors,
+        errors,
+    })
+}
+
+/// Lines of decoration name and owned count.
+/// Fails only if the file can't be read, invalid rows are reported in `errors`
+pub fn parse_deco_inventory(
+    filename: &str,
+    deco_name_dict: &NameDict,
+    lang: Option<&str>,
+) -> Result<CsvImport<OwnedDeco>, String> {
+    let (records, mut errors) = read_csv_records(filename)?;
+
+    let mut owned_decos = Vec::new();
+
+    for record in &records {
+        let mut row = CsvRow::new(record, lang);
+
+        let deco_id = row.get_id(0, deco_name_dict, CsvImportErrorKind::UnknownDecoration);
+        let count = row.get_i32(1);
+
+        if count < 0 {
+            let value = row.get_str(1);
+            row.add_error(1, CsvImportErrorKind::InvalidNumber, value, None);
+        }
+
+        if !row.errors.is_empty() {
+            errors.append(&mut row.errors);
+            continue;
+        }
+
+        owned_decos.push(OwnedDeco {
+            id: deco_id.unwrap(),
+            count,
+        });
+    }
+
+    debug!(
+        "Decoration inventory parsed - count : {}, errors: {}",
+        owned_decos.len(),
+        errors.len()
+    );
+
+    Ok(CsvImport {
+        values: owned_decos,
+        errors,
+    })
+}
+
+pub fn create_data_manager(
+    armors_filename: &str,
+    skills_filename: &str,
+    decos_filename: &str,
+) -> Result<DataManager, String> {
+    let (dm, _) = create_data_manager_with_overrides(
+        armors_filename,
+        skills_filename,
+        decos_filename,
+        &Vec::new(),
+    )?;
+
+    Ok(dm)
+}
+
+/// Data files with overrides applied
+pub struct LoadedData {
+    pub armors: Vec<BaseArmor>,
+    pub skills: Vec<Skill>,
+    pub decos: Vec<Decoration>,
+
+    /// Skipped overrides as warnings, followed by the issues of the resulting data
+    pub report: DataReport,
+}
+
+/// Applies override files in order over the data files, later ones winning, then validates the result.
+/// Fails only if a data file can't be read.
+pub fn load_data(
+    armors_filename: &str,
+    skills_filename: &str,
+    decos_filename: &str,
+    override_files: &Vec<OverrideFile>,
+) -> Result<LoadedData, String> {
+    let mut armors = parse_data::<BaseArmor>(armors_filename)?;
+    let mut skills = parse_data::<Skill>(skills_filename)?;
+    let mut decos = parse_data::<Decoration>(decos_filename)?;
+
+    let mut report = DataReport::default();
+
+    for override_file in override_files {
+        let source = override_file.filename.to_string_lossy();
+
+        let patches = match override_file.read_patches() {
+            Ok(patches) => patches,
+            Err(message) => {
+                report.add(IssueSeverity::Warning, "override", &source, message);
+                continue;
+            }
+        };
+
+        let kind = &override_file.kind;
+        let replaced_keys = kind.replaced_keys();
+
+        let errors = match kind {
+            OverrideKind::Armor => {
+                apply_patches(&mut armors, |armor| armor.id(), replaced_keys, &patches)
+            }
+            OverrideKind::Skill => {
+                apply_patches(&mut skills, |skill| &skill.id, replaced_keys, &patches)
+            }
+            OverrideKind::Deco => {
+                apply_patches(&mut decos, |deco| &deco.id, replaced_keys, &patches)
+            }
+        };
+
+        debug!(
+            "Override applied - file: {}, entries: {}, errors: {}",
+            source,
+            patches.len(),
+            errors.len()
+        );
+
+        for message in errors {
+            report.add(IssueSeverity::Warning, "override", &source, message);
+        }
+    }
+
+    let mut data_report = validate_data(&armors, &skills, &decos);
+    data_report.issues.splice(0..0, report.issues);
+
+    Ok(LoadedData {
+        armors,
+        skills,
+        decos,
+        report: data_report,
+    })
+}
+
+/// Loads and validates data, failing with the errors of the report if any.
+/// The report is returned for its warnings otherwise.
+pub fn create_data_manager_with_overrides(
+    armors_filename: &str,
+    skills_filename: &str,
+    decos_filename: &str,
+    override_files: &Vec<OverrideFile>,
+) -> Result<(DataManager, DataReport), String> {
+    let loaded = load_data(
+        armors_filename,
+        skills_filename,
+        decos_filename,
+        override_files,
+    )?;
+
+    let report = loaded.report;
+
+    let errors = report.get_errors();
+
+    if !errors.is_empty() {
+        let lines = errors
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<String>>();
+
+        return Err(format!(
+            "Invalid data, {} error(s):\n{}",
+            lines.len(),
+            lines.join("\n")
+        ));
+    }
+
+    let mut armors_vec = loaded.armors;
+
+    BaseArmor::assign_series(&mut armors_vec);
+
+    let mut armors = HashMap::<String, BaseArmor>::new();
+    let mut skills = HashMap::<String, Skill>::new();
+    let mut decos = HashMap::<String, Decoration>::new();
+
+    for armor in armors_vec {
+        armors.insert(armor.id().clone(), armor);
+    }
+
+    for skill in loaded.skills {
+        skills.insert(skill.id.clone(), skill);
+    }
+
+    for deco in loaded.decos {
+        decos.insert(deco.id.clone(), deco);
+    }
+
+    let dm = DataManager::new(armors, skills, decos)?;
+
+    Ok((dm, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_anomaly, parse_talisman, CsvImportError, CsvImportErrorKind};
+    use crate::test::get_data_manager;
+
+    #[test]
+    fn csv_import_reports_invalid_rows() {
+        let dm = get_data_manager();
+
+        let filename = std::env::temp_dir().join("mhr_sim_test_talisman.csv");
+        let filename = filename.to_str().unwrap();
+
+        std::fs::write(
+            filename,
+            "Attack Boost,2,Critical Eye,1,2,1,0\n\
+             Atack Boost,2,,0,1,0,0\n\
+             Critical Eye,x,,0,1,0,0\n",
+        )
+        .unwrap();
+
+        let talismans = parse_talisman(filename, &dm.skill_name_dict, None).unwrap();
+
+        std::fs::remove_file(filename).unwrap();
+
+        assert_eq!(talismans.values.len(), 1);
+        assert_eq!(talismans.values[0].id(), "talisman_0");
+        assert_eq!(
+            talismans.errors,
+            vec![
+                CsvImportError {
+                    line: 2,
+                    column: 1,
+                    kind: CsvImportErrorKind::UnknownSkill,
+                    value: "Atack Boost".to_string(),
+                    closest_name: Some("Attack Boost".to_string()),
+                },
+                CsvImportError {
+                    line: 3,
+                    column: 2,
+                    kind: CsvImportErrorKind::InvalidNumber,
+                    value: "x".to_string(),
+                    closest_name: None,
+                },
+            ]
+        );
+
+        let anomaly_filename = std::env::temp_dir().join("mhr_sim_test_anomaly.csv");
+        let anomaly_filename = anomaly_filename.to_str().unwrap();
+
+        std::fs::write(
+            anomaly_filename,
+            "Silver Solhelm,10,0,0,0,0,0,1,0,0,Attack Boost,1\n\
+             Silver Solhlem,10,0,0,0,0,0,1,0,0\n",
+        )
+        .unwrap();
+
+        let anomalies = parse_anomaly(
+            anomaly_filename,
+            &dm.armors,
+            &dm.armor_name_dict,
+            &dm.skill_name_dict,
+            None,
+        )
+        .unwrap();
+
+        std::fs::remove_file(anomaly_filename).unwrap();
+
+        assert_eq!(anomalies.values.len(), 1);
+        assert_eq!(anomalies.errors.len(), 1);
+        assert_eq!(anomalies.errors[0].kind, CsvImportErrorKind::UnknownArmor);
+        assert_eq!(
+            anomalies.errors[0].closest_name,
+            Some("Silver Solhelm".to_string())
+        );
+
+        assert!(parse_talisman("not_existing.csv", &dm.skill_name_dict, None).is_err());
+    }
+}
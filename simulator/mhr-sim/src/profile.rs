@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::data::armor::SexType;
+use crate::search::{SearchLimits, SearchRequest, DEFAULT_LANG};
+
+pub static PROFILE_FILENAME: &str = "profile.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HunterProfile {
+    /// `SexType::All` means either, armors of both sex types are searched
+    #[serde(rename = "sexType")]
+    pub sex_type: SexType,
+
+    pub lang: String,
+
+    #[serde(rename = "weaponSlots")]
+    pub weapon_slots: Vec<i32>,
+
+    pub limits: SearchLimits,
+}
+
+impl Default for HunterProfile {
+    fn default() -> Self {
+        Self {
+            sex_type: SexType::All,
+            lang: DEFAULT_LANG.to_string(),
+            weapon_slots: vec![0, 0, 0],
+            limits: Default::default(),
+        }
+    }
+}
+
+impl HunterProfile {
+    /// Missing or unreadable profile falls back to the default one
+    pub fn load(filename: &Path) -> Self {
+        let file = File::open(filename);
+
+        match file {
+            Ok(file) => {
+                let reader = BufReader::new(file);
+
+                match serde_json::from_reader(reader) {
+                    Ok(profile) => profile,
+                    Err(err) => {
+                        debug!("Invalid profile {:?}: {}", filename, err);
+                        Self::default()
+                    }
+                }
+            }
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, filename: &Path) -> io::Result<()> {
+        if let Some(dir) = filename.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = File::create(filename)?;
+
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    pub fn create_request(
+        &self,
+        weapon_slots: Option<Vec<i32>>,
+        selected_skills: HashMap<String, i32>,
+        free_slots: Vec<i32>,
+    ) -> SearchRequest {
+        let weapon_slots = weapon_slots.unwrap_or_else(|| self.weapon_slots.clone());
+
+        let mut request = SearchRequest::new(
+            weapon_slots,
+            selected_skills,
+            free_slots,
+            self.sex_type.clone(),
+        );
+
+        request.lang = self.lang.clone();
+        request.limits = self.limits.clone();
+
+        request
+    }
+}
@@ -0,0 +1,196 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::search::ResultFullEquipments;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchPhase {
+    /// Combinations of armors with unique skills
+    #[serde(rename = "uniqueArmors")]
+    UniqueArmors,
+    /// Empty armors expanded to armors with decoration skills and slots
+    #[serde(rename = "expandArmors")]
+    ExpandArmors,
+    /// Candidates with possible decoration combinations
+    #[serde(rename = "candidates")]
+    Candidates,
+    /// Candidates expanded to real armors
+    #[serde(rename = "answers")]
+    Answers,
+    #[serde(rename = "done")]
+    Done,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchProgress {
+    pub phase: SearchPhase,
+
+    /// Processed count in the phase, or the resulting count once the phase is finished
+    pub count: usize,
+
+    /// Count to be processed in the phase, same as `count` once the phase is finished
+    pub total: usize,
+
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: u64,
+}
+
+type ResultListener<'f> = Box<dyn Fn(&ResultFullEquipments) + Send + Sync + 'f>;
+
+/// Reports progress of a search and tells it to stop when cancelled
+pub struct SearchMonitor<'f> {
+    cancelled: Arc<AtomicBool>,
+    on_progress: Box<dyn Fn(&SearchProgress) + Send + Sync + 'f>,
+    on_result: Option<ResultListener<'f>>,
+    start_time: Instant,
+}
+
+impl<'f> SearchMonitor<'f> {
+    pub fn new<F>(cancelled: Arc<AtomicBool>, on_progress: F) -> Self
+    where
+        F: Fn(&SearchProgress) + Send + Sync + 'f,
+    {
+        Self {
+            cancelled,
+            on_progress: Box::new(on_progress),
+            on_result: None,
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Receives full equipments as soon as they are found, in no particular order.
+    /// Called from multiple threads, may receive sets not in the final (limited) result.
+    /// Only the first page of a search without `sort_by` is streamed.
+    pub fn with_result_listener<F>(mut self, on_result: F) -> Self
+    where
+        F: Fn(&ResultFullEquipments) + Send + Sync + 'f,
+    {
+        self.on_result = Some(Box::new(on_result));
+        self
+    }
+
+    pub fn streams_results(&self) -> bool {
+        self.on_result.is_some()
+    }
+
+    pub fn send_result(&self, result: &ResultFullEquipments) {
+        if let Some(on_result) = &self.on_result {
+            on_result(result);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn report(&self, phase: SearchPhase, count: usize, total: usize) {
+        let progress = SearchProgress {
+            phase,
+            count,
+            total,
+            elapsed_ms: self.start_time.elapsed().as_millis() as u64,
+        };
+
+        (self.on_progress)(&progress);
+    }
+
+    /// Reports about every percent of the phase, for loops calling it on each item
+    pub fn report_partial(&self, phase: SearchPhase, count: usize, total: usize) {
+        let step = (total / 100).max(1);
+
+        if count % step == 0 || count == total {
+            self.report(phase, count, total);
+        }
+    }
+}
+
+impl<'f> Default for SearchMonitor<'f> {
+    fn default() -> Self {
+        Self::new(Arc::new(AtomicBool::new(false)), |_| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use super::{SearchMonitor, SearchPhase};
+    use crate::{
+        data::armor::StatType,
+        search::search_with_monitor,
+        test::{create_pinned_request, get_data_manager, get_equip_ids},
+    };
+
+    #[test]
+    fn search_progress_and_cancel() {
+        let dm = get_data_manager();
+
+        let mut request = create_pinned_request(&[("critical_boost", 3), ("agitator", 3)]);
+
+        // Streamed candidates are the ones found first, which may differ with limits
+        request.limits.max_results = 100000;
+
+        let phases = Mutex::new(Vec::new());
+        let streamed = Mutex::new(Vec::new());
+
+        let monitor = SearchMonitor::new(Arc::new(AtomicBool::new(false)), |progress| {
+            phases.lock().unwrap().push(progress.phase)
+        })
+        .with_result_listener(|equip| streamed.lock().unwrap().push(get_equip_ids(equip)));
+
+        let result = search_with_monitor(dm, &request, &monitor)
+            .unwrap()
+            .unwrap();
+        drop(monitor);
+        let phases = phases.into_inner().unwrap();
+        let streamed = streamed.into_inner().unwrap();
+
+        assert_eq!(phases.first(), Some(&SearchPhase::UniqueArmors));
+        assert_eq!(phases.last(), Some(&SearchPhase::Done));
+        assert_ne!(result.full_equipments.len(), 0);
+
+        // All candidates fit in the limit, so the same sets are streamed in another order
+        let mut result_ids = result
+            .full_equipments
+            .iter()
+            .map(get_equip_ids)
+            .collect::<Vec<String>>();
+        let mut streamed_ids = streamed.clone();
+
+        result_ids.sort();
+        streamed_ids.sort();
+
+        assert_eq!(result_ids, streamed_ids);
+
+        // Later pages and sorted results depend on all candidates, so nothing is streamed
+        for (cursor, sort_by) in [(Some(1), None), (None, Some(StatType::Defense))] {
+            let mut request = request.clone();
+            request.cursor = cursor;
+            request.sort_by = sort_by;
+
+            let streamed_count = AtomicUsize::new(0);
+
+            let monitor = SearchMonitor::new(Arc::new(AtomicBool::new(false)), |_| {})
+                .with_result_listener(|_| {
+                    streamed_count.fetch_add(1, Ordering::Relaxed);
+                });
+
+            let result = search_with_monitor(dm, &request, &monitor)
+                .unwrap()
+                .unwrap();
+
+            assert_ne!(result.full_equipments.len(), 0);
+            assert_eq!(streamed_count.load(Ordering::Relaxed), 0);
+        }
+
+        let cancelled = SearchMonitor::new(Arc::new(AtomicBool::new(true)), |_| {});
+
+        assert!(search_with_monitor(dm, &request, &cancelled)
+            .unwrap()
+            .is_none());
+    }
+}
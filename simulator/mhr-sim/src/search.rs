@@ -0,0 +1,1728 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use itertools::iproduct;
+use log::{debug, info};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::calc::armor::CalcArmor;
+use crate::calc::calc_equipment::CalcEquipment;
+use crate::calc::deco::CalcDeco;
+use crate::calc::talisman::CalcTalisman;
+use crate::constraints::SearchConstraints;
+use crate::data::armor::{ArmorPart, ArmorStat, BaseArmor, SexType, StatType, EMPTY_ARMOR_PREFIX};
+use crate::data::data_manager::DataManager;
+use crate::data::deco::Decoration;
+use crate::data::deco_combination::DecorationCombination;
+use crate::data::skill::MAX_SLOT_LEVEL;
+use crate::deco_inventory::DecoInventory;
+use crate::deco_placement::{place_decorations, ResultDecorationSlot};
+use crate::full_equipments::FullEquipments;
+use crate::progress::{SearchMonitor, SearchPhase};
+use crate::BoxCalcEquipment;
+
+/// Default count of full equipments per page
+pub static MAX_ANSWER_LENGTH: usize = 200;
+pub static MAX_CANDIDATES: usize = 10000;
+pub static DEFAULT_LANG: &str = "ko";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchLimits {
+    /// Maximum number of armor combinations verified against decorations, best ones first
+    #[serde(rename = "maxCandidates", default = "default_max_candidates")]
+    pub max_candidates: usize,
+
+    /// Maximum number of full equipments in a result page
+    #[serde(rename = "maxResults", default = "default_max_results")]
+    pub max_results: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchRequest {
+    #[serde(rename = "weaponSlots")]
+    pub weapon_slots: Vec<i32>,
+
+    #[serde(rename = "selectedSkills")]
+    pub selected_skills: HashMap<String, i32>,
+
+    #[serde(rename = "freeSlots")]
+    pub free_slots: Vec<i32>,
+
+    #[serde(rename = "sexType")]
+    pub sex_type: SexType,
+
+    /// Language of armor names in debug output
+    #[serde(default = "default_lang")]
+    pub lang: String,
+
+    #[serde(default)]
+    pub limits: SearchLimits,
+
+    #[serde(default)]
+    pub constraints: SearchConstraints,
+
+    /// Results are sorted by the total stat in descending order if set
+    #[serde(rename = "sortBy", default)]
+    pub sort_by: Option<StatType>,
+
+    /// Offset of the first result, from `next_cursor` of the previous page
+    #[serde(default)]
+    pub cursor: Option<usize>,
+
+    /// Owned decorations, every decoration is available in any count if not set
+    #[serde(rename = "decoInventory", default)]
+    pub deco_inventory: Option<DecoInventory>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SearchResult {
+    pub log: String,
+    pub full_equipments: Vec<ResultFullEquipments>,
+
+    /// Cursor of the next page, `None` on the last page
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<usize>,
+
+    /// Reasons why some results are left out, empty if the result is complete
+    pub truncations: Vec<Truncation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "reason")]
+pub enum Truncation {
+    /// Candidates with lower points than the first `limit` were not verified, with results still needed
+    #[serde(rename = "candidateLimit")]
+    CandidateLimit { limit: usize, total: usize },
+    /// Only a page of `limit` results from `offset` is included, see `next_cursor`
+    #[serde(rename = "resultLimit")]
+    ResultLimit { limit: usize, offset: usize },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResultFullEquipments {
+    pub armors: HashMap<String, ResultArmor>,
+    pub talisman: ResultTalisman,
+    pub deco_combs: Vec<ResultDecorationCombination>,
+    pub stat: ArmorStat,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResultArmor {
+    /// Id of the armor worn, an anomaly armor id if anomaly crafted
+    pub id: String,
+    pub base_id: String,
+    pub is_anomaly: bool,
+    /// Not owned according to the armor inventory
+    pub needs_crafting: bool,
+    pub stat: ArmorStat,
+
+    pub skills: HashMap<String, i32>,
+    pub slots: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResultTalisman {
+    pub id: String,
+    pub skills: HashMap<String, i32>,
+    pub slots: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResultDecorationCombination {
+    pub skills: HashMap<String, Vec<i32>>,
+    pub slots_sum: Vec<i32>,
+    /// Every slot of the equipments with the decoration to put in it
+    pub placement: Vec<ResultDecorationSlot>,
+    /// Decorations of the placement not owned, with the missing count of each (shopping list)
+    pub missing_decos: HashMap<String, i32>,
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self {
+            max_candidates: MAX_CANDIDATES,
+            max_results: MAX_ANSWER_LENGTH,
+        }
+    }
+}
+
+impl SearchRequest {
+    pub fn new(
+        weapon_slots: Vec<i32>,
+        selected_skills: HashMap<String, i32>,
+        free_slots: Vec<i32>,
+        sex_type: SexType,
+    ) -> Self {
+        Self {
+            weapon_slots,
+            selected_skills,
+            free_slots,
+            sex_type,
+            lang: DEFAULT_LANG.to_string(),
+            limits: Default::default(),
+            constraints: Default::default(),
+            sort_by: None,
+            cursor: None,
+            deco_inventory: None,
+        }
+    }
+
+    /// Skill levels and slots the search relies on, checked against the data
+    pub fn validate(&self, dm: &DataManager) -> Result<(), String> {
+        check_skill_levels(dm, &self.selected_skills)?;
+
+        for slot_size in &self.weapon_slots {
+            if *slot_size < 0 || (MAX_SLOT_LEVEL as i32) < *slot_size {
+                return Err(format!(
+                    "Weapon slot size {} is out of 0 to {}",
+                    slot_size, MAX_SLOT_LEVEL
+                ));
+            }
+        }
+
+        check_free_slots(&self.free_slots)
+    }
+}
+
+/// Free slots are counts per slot size
+pub(crate) fn check_free_slots(free_slots: &[i32]) -> Result<(), String> {
+    if free_slots.len() != MAX_SLOT_LEVEL {
+        return Err(format!(
+            "{} free slot counts instead of {}",
+            free_slots.len(),
+            MAX_SLOT_LEVEL
+        ));
+    }
+
+    if free_slots.iter().any(|count| *count < 0) {
+        return Err(format!("Negative free slot count: {:?}", free_slots));
+    }
+
+    Ok(())
+}
+
+/// Decoration combinations exist only for levels 1 to the max level of each skill
+pub(crate) fn check_skill_levels(
+    dm: &DataManager,
+    skills: &HashMap<String, i32>,
+) -> Result<(), String> {
+    for (skill_id, level) in skills {
+        let skill = match dm.skills.get(skill_id) {
+            Some(skill) => skill,
+            None => return Err(format!("Unknown skill {}", skill_id)),
+        };
+
+        if *level < 1 || skill.max_level < *level {
+            return Err(format!(
+                "Skill {} level {} is out of 1 to {}",
+                skill_id, level, skill.max_level
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn default_lang() -> String {
+    DEFAULT_LANG.to_string()
+}
+
+fn default_max_candidates() -> usize {
+    MAX_CANDIDATES
+}
+
+fn default_max_results() -> usize {
+    MAX_ANSWER_LENGTH
+}
+
+/// Sum of armor stats, talismans have no stat
+fn get_total_stat<'a>(equipments: &Vec<BoxCalcEquipment<'a>>) -> ArmorStat {
+    let mut total_stat = ArmorStat::default();
+
+    for equipment in equipments {
+        if equipment.part() == &ArmorPart::Talisman {
+            continue;
+        }
+
+        total_stat.add(equipment.as_armor().stat());
+    }
+
+    total_stat
+}
+
+pub(crate) fn check_static_conditions<'a>(
+    dm: &DataManager,
+    weapon_slots: &Vec<i32>,
+    req_skills: &HashMap<String, i32>,
+    req_slots: &[i32],
+    deco_inventory: Option<&DecoInventory>,
+    equipments: &[&BoxCalcEquipment<'a>],
+) -> Option<(HashMap<String, i32>, Vec<i32>)> {
+    let mut req_skills = req_skills.clone();
+    let mut req_slots = req_slots.to_vec();
+
+    let real_parts = vec![
+        equipments[0].clone(),
+        equipments[1].clone(),
+        equipments[2].clone(),
+        equipments[3].clone(),
+        equipments[4].clone(),
+        equipments[5].clone(),
+    ];
+
+    for part in real_parts.iter() {
+        part.subtract_skills(&mut req_skills);
+    }
+
+    let (no_deco_skills, single_deco_skills, _) = dm.get_skils_by_deco(&req_skills);
+
+    if !no_deco_skills.is_empty() {
+        return None;
+    }
+
+    if let Some(deco_inventory) = deco_inventory {
+        for (skill_id, (_, level)) in &single_deco_skills {
+            if !deco_inventory.allows_single_deco(dm, skill_id, *level) {
+                return None;
+            }
+        }
+    }
+
+    let single_deco_skills = single_deco_skills
+        .iter()
+        .map(|(id, (slot_size, count))| (id, *slot_size, *count))
+        .collect::<Vec<(&String, i32, i32)>>();
+
+    let single_decos_as_slots = CalcDeco::convert_to_slots(&single_deco_skills);
+
+    for (slot_size_index, count) in single_decos_as_slots.iter().enumerate() {
+        req_slots[slot_size_index] += count;
+    }
+
+    let (_, mut avail_slots) = FullEquipments::calculate_skills_slots(weapon_slots, &real_parts);
+
+    let slot_success =
+        DecorationCombination::is_possible_static_mut(&mut avail_slots, &mut req_slots);
+
+    if !slot_success {
+        return None;
+    }
+
+    for (id, _, _) in &single_deco_skills {
+        req_skills.remove(*id);
+    }
+
+    // This only calculates the number of slots regardless of slot size, just for candidate optimization
+    let mut minimum_slot_sum = 0;
+
+    for (skill_id, &level) in &req_skills {
+        let mut deco_sum_per_level = dm
+            .deco_combinations
+            .get(skill_id)
+            .unwrap()
+            .get(level as usize - 1)
+            .unwrap()
+            .iter()
+            .map(|comb| comb.iter().sum::<i32>())
+            .collect::<Vec<i32>>();
+
+        deco_sum_per_level.sort();
+
+        minimum_slot_sum += deco_sum_per_level[0];
+    }
+
+    let equip_slot_sum = avail_slots.iter().sum::<i32>();
+
+    if equip_slot_sum < minimum_slot_sum {
+        return None;
+    }
+
+    Some((req_skills, avail_slots))
+}
+
+/// Some decoration combination fits the slots, and the owned decorations unless unlimited
+pub(crate) fn has_possible_deco_combs(
+    dm: &DataManager,
+    deco_inventory: Option<&DecoInventory>,
+    req_skills: &HashMap<String, i32>,
+    avail_slots: &Vec<i32>,
+) -> bool {
+    match deco_inventory {
+        Some(deco_inventory) => dm
+            .deco_combinations
+            .iter_possible_combs(req_skills, |comb| {
+                comb.is_possible(avail_slots) && deco_inventory.allows_comb(dm, req_skills, &comb)
+            }),
+        None => dm
+            .deco_combinations
+            .has_possible_combs(req_skills, avail_slots),
+    }
+}
+
+/// Fails if the request doesn't pass `SearchRequest::validate`
+pub fn search(dm: &DataManager, request: &SearchRequest) -> Result<SearchResult, String> {
+    let result = search_with_monitor(dm, request, &SearchMonitor::default())?;
+
+    Ok(result.expect("Search without cancel flag is never cancelled"))
+}
+
+/// Search reporting its progress to `monitor`, `None` if cancelled
+pub fn search_with_monitor<'a>(
+    dm: &'a DataManager,
+    request: &SearchRequest,
+    monitor: &SearchMonitor,
+) -> Result<Option<SearchResult>, String> {
+    request.validate(dm)?;
+
+    let weapon_slots = &request.weapon_slots;
+    let selected_skills = &request.selected_skills;
+    let free_slots = &request.free_slots;
+    let sex_type = &request.sex_type;
+    let constraints = &request.constraints;
+    let max_candidates = request.limits.max_candidates;
+
+    let start_time = Instant::now();
+    let mut ret = String::from("\n");
+
+    let mut decos_possible = HashMap::<String, Vec<&Decoration>>::new();
+
+    let (no_deco_skills, yes_deco_skills) = dm.get_leftover_skills(selected_skills);
+
+    for skill_id in selected_skills.keys() {
+        let decos = dm.get_deco_by_skill_id(skill_id);
+
+        if !decos.is_empty() {
+            decos_possible.insert(skill_id.clone(), decos);
+        }
+    }
+
+    let decos_possible = decos_possible;
+
+    info!("Skills with yes deco: {:?}", yes_deco_skills);
+    info!("Skills with no deco: {:?}", no_deco_skills);
+
+    let helms = dm.get_parts(ArmorPart::Helm);
+    let torsos = dm.get_parts(ArmorPart::Torso);
+    let arms = dm.get_parts(ArmorPart::Arm);
+    let waists = dm.get_parts(ArmorPart::Waist);
+    let feets = dm.get_parts(ArmorPart::Feet);
+
+    let mut base_armors = HashMap::<ArmorPart, Vec<&BaseArmor>>::new();
+
+    base_armors.insert(ArmorPart::Helm, helms);
+    base_armors.insert(ArmorPart::Torso, torsos);
+    base_armors.insert(ArmorPart::Arm, arms);
+    base_armors.insert(ArmorPart::Waist, waists);
+    base_armors.insert(ArmorPart::Feet, feets);
+
+    let mut all_armors = HashMap::<ArmorPart, Vec<CalcArmor<'a>>>::new();
+    let mut armors_count_before = 0;
+
+    for (part, base_armors) in base_armors.iter() {
+        let mut calc_armors = Vec::new();
+
+        for base_armor in base_armors {
+            let anomaly_base = dm.get_anomaly_armor(base_armor.id());
+
+            let calc_armor = match anomaly_base {
+                Some(anomaly_armor) => {
+                    CalcArmor::<'a>::new_anomaly(&anomaly_armor.affected, &anomaly_armor.original)
+                }
+                None => CalcArmor::<'a>::new_anomaly(base_armor, base_armor),
+            };
+
+            calc_armors.push(calc_armor);
+            armors_count_before += 1;
+        }
+
+        all_armors.insert(part.clone(), calc_armors);
+    }
+
+    for (_, armors) in all_armors.iter_mut() {
+        *armors = armors
+            .iter_mut()
+            .filter_map(|armor| {
+                if armor.sex_type().is_wearable_by(sex_type)
+                    && constraints.allows_armor(armor.base(), armor.original(), &dm.armor_inventory)
+                {
+                    Some(armor.clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<CalcArmor<'a>>>();
+
+        armors.sort_by_key(|armor| {
+            armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills)
+        });
+    }
+
+    let all_talismans = dm
+        .talismans
+        .iter()
+        .filter(|tali| constraints.allows_talisman(tali))
+        .map(CalcTalisman::<'a>::new)
+        .collect::<Vec<CalcTalisman<'a>>>();
+
+    let talisman_count_before = all_talismans.len();
+
+    debug!(
+        "Armors count: {}, talisman count: {}",
+        armors_count_before, talisman_count_before
+    );
+
+    let mut all_slot_equips = HashMap::<ArmorPart, HashMap<String, BoxCalcEquipment<'a>>>::new();
+
+    for (part, _) in all_armors.iter() {
+        let slot_only_armors = dm.slot_only_armors.get(part).unwrap();
+        let mut part_slot_armors = HashMap::<String, BoxCalcEquipment<'a>>::new();
+
+        for (id, armor) in slot_only_armors {
+            if constraints.is_pinned(part) {
+                break;
+            }
+
+            let has_allowed_armor = dm.armors_by_slot[part][id].iter().any(|base_armor| {
+                let (armor, original) = dm.get_armor_by_id(base_armor.id()).unwrap();
+
+                armor.sex_type.is_wearable_by(sex_type)
+                    && constraints.allows_armor(armor, original, &dm.armor_inventory)
+            });
+
+            if !has_allowed_armor {
+                continue;
+            }
+
+            let calc_armor = CalcArmor::<'a>::new(armor).clone_dyn();
+
+            part_slot_armors.insert(id.clone(), calc_armor);
+        }
+
+        all_slot_equips.insert(part.clone(), part_slot_armors);
+    }
+
+    all_slot_equips.insert(
+        ArmorPart::Talisman,
+        dm.slot_only_talismans
+            .iter()
+            .filter(|(id, _)| {
+                !constraints.is_pinned(&ArmorPart::Talisman)
+                    && dm.talismans_by_slot[*id]
+                        .iter()
+                        .any(|tali| constraints.allows_talisman(tali))
+            })
+            .map(|(id, tali)| (id.clone(), CalcTalisman::new(tali).clone_dyn()))
+            .collect::<HashMap<String, BoxCalcEquipment<'a>>>(),
+    );
+
+    let mut equips_with_deco_skills = HashMap::<&ArmorPart, Vec<BoxCalcEquipment<'a>>>::new();
+
+    for (part, armors) in &all_armors {
+        let mut part_armors = Vec::<BoxCalcEquipment<'a>>::new();
+
+        for armor in armors {
+            for skill_id in decos_possible.keys() {
+                if armor.skills().contains_key(skill_id) {
+                    part_armors.push(armor.clone_dyn());
+                    break;
+                }
+            }
+        }
+
+        equips_with_deco_skills.insert(part, part_armors);
+    }
+
+    let mut tali_with_deco_skills = Vec::new();
+
+    for tali in &all_talismans {
+        for skill_id in decos_possible.keys() {
+            if tali.skills().contains_key(skill_id) {
+                tali_with_deco_skills.push(tali.clone_dyn());
+                break;
+            }
+        }
+    }
+
+    equips_with_deco_skills.insert(&ArmorPart::Talisman, tali_with_deco_skills);
+
+    let mut all_unique_armors = HashMap::<&ArmorPart, Vec<BoxCalcEquipment<'a>>>::new();
+
+    for (part, armors) in &all_armors {
+        // Pinned part has only the pinned armor left, so it is used as is regardless of skills
+        if constraints.is_pinned(part) {
+            all_unique_armors.insert(part, armors.iter().map(|armor| armor.clone_dyn()).collect());
+            continue;
+        }
+
+        all_unique_armors.insert(
+            part,
+            armors
+                .iter()
+                .filter_map(|armor| {
+                    for skill_id in no_deco_skills.keys() {
+                        if armor.skills().contains_key(skill_id) {
+                            return Some(armor.clone_dyn());
+                        }
+                    }
+
+                    None
+                })
+                .collect::<Vec<BoxCalcEquipment<'a>>>(),
+        );
+
+        all_unique_armors
+            .get_mut(part)
+            .unwrap()
+            .push(CalcArmor::<'a>::new(dm.empty_armors.get(part).unwrap()).clone_dyn());
+    }
+
+    all_unique_armors.insert(
+        &ArmorPart::Talisman,
+        all_talismans
+            .iter()
+            .filter_map(|tali| {
+                for skill_id in no_deco_skills.keys() {
+                    if tali.skills().contains_key(skill_id) {
+                        return Some(tali.clone_dyn());
+                    }
+                }
+
+                None
+            })
+            .collect(),
+    );
+
+    if constraints.is_pinned(&ArmorPart::Talisman) {
+        all_unique_armors.insert(
+            &ArmorPart::Talisman,
+            all_talismans.iter().map(|tali| tali.clone_dyn()).collect(),
+        );
+    } else {
+        all_unique_armors
+            .get_mut(&ArmorPart::Talisman)
+            .unwrap()
+            .push(CalcTalisman::new(&dm.empty_talisman).clone_dyn());
+    }
+
+    for (_, unique_armors) in all_unique_armors.iter_mut() {
+        // Ties are ordered by id, so the order doesn't depend on hash map iteration
+        unique_armors.sort_by_key(|armor| {
+            (
+                armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills),
+                armor.id().clone(),
+            )
+        });
+    }
+
+    // Collected in the iteration order of helms, sorting afterwards is stable
+    let mut possible_unique_equips = all_unique_armors[&ArmorPart::Helm]
+        .par_iter()
+        .filter(|_| !monitor.is_cancelled())
+        .flat_map_iter(|helm| {
+            iproduct!(
+                all_unique_armors[&ArmorPart::Torso].iter(),
+                all_unique_armors[&ArmorPart::Arm].iter(),
+                all_unique_armors[&ArmorPart::Waist].iter(),
+                all_unique_armors[&ArmorPart::Feet].iter(),
+                all_unique_armors[&ArmorPart::Talisman].iter()
+            )
+            .filter_map(|(torso, arm, waist, feet, tali)| {
+                let equips: Vec<BoxCalcEquipment<'a>> = vec![
+                    helm.clone_dyn(),
+                    torso.clone_dyn(),
+                    arm.clone_dyn(),
+                    waist.clone_dyn(),
+                    feet.clone_dyn(),
+                    tali.clone_dyn(),
+                ];
+
+                let full_equip = FullEquipments::<'a>::new(weapon_slots.clone(), equips.clone());
+                let possible_result = full_equip.contains_skills(&no_deco_skills.clone());
+
+                if !possible_result {
+                    return None;
+                }
+
+                debug!(
+                    "Unique skills possible: {:?}",
+                    vec![
+                        helm.id(),
+                        torso.id(),
+                        arm.id(),
+                        waist.id(),
+                        feet.id(),
+                        tali.id()
+                    ]
+                );
+
+                Some(equips)
+            })
+        })
+        .collect::<Vec<Vec<BoxCalcEquipment<'a>>>>();
+
+    possible_unique_equips.sort_by_key(|armors| {
+        let mut sum = 0;
+
+        for armor in armors {
+            sum += armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills);
+        }
+
+        Reverse(sum)
+    });
+
+    if monitor.is_cancelled() {
+        return Ok(None);
+    }
+
+    monitor.report(
+        SearchPhase::UniqueArmors,
+        possible_unique_equips.len(),
+        possible_unique_equips.len(),
+    );
+
+    ret.push_str(&format!(
+        "Unique armors count: {}, calculation: {:?}\n",
+        possible_unique_equips.len(),
+        start_time.elapsed()
+    ));
+
+    info!(
+        "Unique armors count: {}, calculation: {:?}",
+        possible_unique_equips.len(),
+        start_time.elapsed()
+    );
+
+    let result_stream = ResultStream::new(monitor, request);
+
+    // Armors of each part to combine for each unique equipment, in part order
+    let unique_parts = possible_unique_equips
+        .par_iter()
+        .map(|possible_unique_vec| {
+            let parts = possible_unique_vec
+                .iter()
+                .map(|equipment| {
+                    let part = equipment.part();
+
+                    let mut ret = Vec::<&BoxCalcEquipment<'a>>::new();
+
+                    if equipment.id().starts_with(EMPTY_ARMOR_PREFIX) {
+                        let part_unique_armors = &all_unique_armors[part]
+                            .iter()
+                            .collect::<Vec<&BoxCalcEquipment<'a>>>();
+
+                        let part_deco_armors = &equips_with_deco_skills[part]
+                            .iter()
+                            .collect::<Vec<&BoxCalcEquipment<'a>>>();
+
+                        let part_slot_armors = &all_slot_equips[part]
+                            .values()
+                            .filter(|armor| {
+                                // Slots of calc equipments are counts per slot size, unlike slot armor ids
+                                for unique_armor in part_unique_armors.iter() {
+                                    if unique_armor.slots() == armor.slots() {
+                                        return false;
+                                    }
+                                }
+
+                                for deco_armor in part_deco_armors.iter() {
+                                    if deco_armor.slots() == armor.slots() {
+                                        return false;
+                                    }
+                                }
+
+                                true
+                            })
+                            .collect::<Vec<&BoxCalcEquipment<'a>>>();
+
+                        for armor in part_unique_armors.iter() {
+                            ret.push(armor);
+                        }
+
+                        for armor in part_deco_armors.iter() {
+                            ret.push(armor);
+                        }
+
+                        for armor in part_slot_armors.iter() {
+                            ret.push(armor);
+                        }
+                    } else {
+                        ret.push(equipment);
+                    }
+
+                    // Better armors first, so good sets are found (and streamed) early
+                    ret.sort_by_key(|armor| {
+                        (
+                            Reverse(armor.get_point(
+                                &decos_possible,
+                                &yes_deco_skills,
+                                &no_deco_skills,
+                            )),
+                            armor.id().clone(),
+                        )
+                    });
+
+                    ret
+                })
+                .collect::<Vec<Vec<&BoxCalcEquipment<'a>>>>();
+
+            let mut total_count = 1;
+
+            for part_armors in &parts {
+                total_count *= part_armors.len();
+            }
+
+            debug!(
+                "Parts id: {} {} {} {} {} {}",
+                possible_unique_vec[0].id(),
+                possible_unique_vec[1].id(),
+                possible_unique_vec[2].id(),
+                possible_unique_vec[3].id(),
+                possible_unique_vec[4].id(),
+                possible_unique_vec[5].id(),
+            );
+
+            debug!(
+                "Parts size: {} {} {} {} {} {}, total: {}",
+                parts[0].len(),
+                parts[1].len(),
+                parts[2].len(),
+                parts[3].len(),
+                parts[4].len(),
+                parts[5].len(),
+                total_count,
+            );
+
+            parts
+        })
+        .collect::<Vec<Vec<Vec<&BoxCalcEquipment<'a>>>>>();
+
+    if monitor.is_cancelled() {
+        return Ok(None);
+    }
+
+    // Combinations of each unique equipment are split by the sum of their armor indices,
+    // so combinations of better armors are checked first and all of them run in one parallel loop
+    let rank_jobs = unique_parts
+        .iter()
+        .enumerate()
+        .filter(|(_, parts)| parts.iter().all(|part_armors| !part_armors.is_empty()))
+        .flat_map(|(unique_index, parts)| {
+            let max_rank_sum = parts
+                .iter()
+                .map(|part_armors| part_armors.len() - 1)
+                .sum::<usize>();
+
+            (0..max_rank_sum + 1).map(move |rank_sum| (unique_index, rank_sum))
+        })
+        .collect::<Vec<(usize, usize)>>();
+
+    let processed_job_count = AtomicUsize::new(0);
+
+    // Sets reached from several unique equipments are checked only once
+    let checked_ids = Mutex::new(HashSet::new());
+
+    let rank_cases = rank_jobs
+        .par_iter()
+        .map(|&(unique_index, rank_sum)| {
+            let parts = &unique_parts[unique_index];
+            let mut rank_before_len = 0;
+            let mut rank_cases = Vec::new();
+
+            if monitor.is_cancelled() {
+                return (rank_before_len, rank_cases);
+            }
+
+            let part_sizes = parts
+                .iter()
+                .map(|part_armors| part_armors.len())
+                .collect::<Vec<usize>>();
+
+            for_each_indices_by_rank_sum(&part_sizes, rank_sum, &mut |indices| {
+                if monitor.is_cancelled() {
+                    return false;
+                }
+
+                rank_before_len += 1;
+
+                let equipments = indices
+                    .iter()
+                    .enumerate()
+                    .map(|(part_index, &armor_index)| parts[part_index][armor_index])
+                    .collect::<Vec<&BoxCalcEquipment<'a>>>();
+
+                let full_equip_id = FullEquipments::get_full_equip_id(&equipments);
+
+                if !checked_ids.lock().unwrap().insert(full_equip_id.clone()) {
+                    return true;
+                }
+
+                let multi_deco_leftovers = check_static_conditions(
+                    dm,
+                    weapon_slots,
+                    selected_skills,
+                    free_slots,
+                    request.deco_inventory.as_ref(),
+                    &equipments,
+                );
+
+                if multi_deco_leftovers.is_none() {
+                    return true;
+                }
+
+                let (multi_deco_req_skills, avail_slots) = multi_deco_leftovers.unwrap();
+
+                result_stream.send_candidate(
+                    dm,
+                    request,
+                    &equipments,
+                    &multi_deco_req_skills,
+                    &avail_slots,
+                    &full_equip_id,
+                );
+
+                rank_cases.push((
+                    full_equip_id,
+                    equipments,
+                    multi_deco_req_skills,
+                    avail_slots,
+                ));
+
+                true
+            });
+
+            let processed_count = processed_job_count.fetch_add(1, Ordering::Relaxed) + 1;
+            monitor.report_partial(SearchPhase::ExpandArmors, processed_count, rank_jobs.len());
+
+            (rank_before_len, rank_cases)
+        })
+        .collect::<Vec<_>>();
+
+    if monitor.is_cancelled() {
+        return Ok(None);
+    }
+
+    let mut all_calculate_cases = Vec::new();
+    let mut all_parts_before_len = 0;
+
+    for (rank_before_len, rank_cases) in rank_cases {
+        all_parts_before_len += rank_before_len;
+        all_calculate_cases.extend(rank_cases);
+    }
+
+    // A set is checked by whichever job reaches it first, so the order is fixed by id
+    all_calculate_cases.sort_by(|case1, case2| case1.0.cmp(&case2.0));
+
+    ret.push_str(&format!(
+        "Empty armors expand: before count: {}, after count: {}, time: {:?}\n",
+        all_parts_before_len,
+        all_calculate_cases.len(),
+        start_time.elapsed()
+    ));
+
+    info!(
+        "Empty armors expand: before count: {}, after count: {}, time: {:?}\n",
+        all_parts_before_len,
+        all_calculate_cases.len(),
+        start_time.elapsed()
+    );
+
+    let mut all_loop_tree = BTreeMap::new();
+    let mut total_case_count = 0;
+
+    let possible_cases = all_calculate_cases
+        .par_iter()
+        .filter(|(_, _, multi_deco_req_skills, avail_slots)| {
+            !monitor.is_cancelled()
+                && has_possible_deco_combs(
+                    dm,
+                    request.deco_inventory.as_ref(),
+                    multi_deco_req_skills,
+                    avail_slots,
+                )
+        })
+        .collect::<Vec<_>>();
+
+    if monitor.is_cancelled() {
+        return Ok(None);
+    }
+
+    for (full_equip_id, equipments, multi_deco_req_skills, avail_slots) in possible_cases {
+        debug!(
+            "Possible candidiate: {:?}\nleft skills: {:?}, slots: {:?}",
+            equipments
+                .iter()
+                .map(|part| part.id())
+                .collect::<Vec<&String>>(),
+            multi_deco_req_skills,
+            avail_slots
+        );
+
+        let total_point = CalcDeco::get_point(avail_slots);
+
+        let mut existing = all_loop_tree.get_mut(&Reverse(total_point));
+
+        if existing.is_none() {
+            all_loop_tree.insert(
+                Reverse(total_point),
+                Vec::<(
+                    &String,
+                    &Vec<&BoxCalcEquipment<'a>>,
+                    &Vec<i32>,
+                    &HashMap<String, i32>,
+                )>::new(),
+            );
+            existing = all_loop_tree.get_mut(&Reverse(total_point));
+        }
+
+        existing.unwrap().push((
+            full_equip_id,
+            equipments,
+            avail_slots,
+            multi_deco_req_skills,
+        ));
+
+        total_case_count += 1;
+    }
+
+    monitor.report(SearchPhase::Candidates, total_case_count, total_case_count);
+
+    let mut truncations = Vec::new();
+
+    let verified_case_count = total_case_count.min(max_candidates);
+
+    let elapsed_sort = start_time.elapsed();
+
+    ret.push_str(&format!(
+        "Candidate cases count: {},\nall_loop_cases sorting elapsed: {:?}\n",
+        total_case_count, elapsed_sort
+    ));
+
+    debug!(
+        "Candidate cases calculated, start calculating: {}\n",
+        total_case_count
+    );
+
+    let offset = request.cursor.unwrap_or(0);
+    let max_results = request.limits.max_results;
+
+    // Without sorting by stat, candidates after the requested page are not needed.
+    // Answers are converted (and filtered by placement and inventory) as they are found,
+    // so only results that are really returned count toward the page.
+    let results_needed = match &request.sort_by {
+        Some(_) => usize::MAX,
+        None => offset.saturating_add(max_results).saturating_add(1),
+    };
+
+    let mut processed_case_count = 0;
+    let mut answers_count = 0;
+    let mut result_equipments = Vec::new();
+
+    let all_cases = all_loop_tree.values().flatten().take(verified_case_count);
+
+    for (full_equip_id, real_parts, avail_slots, req_skills) in all_cases {
+        if monitor.is_cancelled() {
+            return Ok(None);
+        }
+
+        if results_needed <= result_equipments.len() {
+            break;
+        }
+
+        processed_case_count += 1;
+
+        // Streamed candidates were already calculated
+        let (case_answers_count, results) = match result_stream.take_results(full_equip_id) {
+            Some(streamed) => streamed,
+            None => get_candidate_results(dm, request, req_skills, real_parts, avail_slots),
+        };
+
+        answers_count += case_answers_count;
+        result_equipments.extend(results);
+
+        monitor.report_partial(
+            SearchPhase::Answers,
+            processed_case_count,
+            verified_case_count,
+        );
+    }
+
+    // Candidates are sorted by point before the limit is applied, so only worse ones are dropped.
+    // They only count as truncated if the limit was reached while results were still needed.
+    if max_candidates < total_case_count
+        && processed_case_count == verified_case_count
+        && result_equipments.len() < results_needed
+    {
+        debug!(
+            "Candidate case count reached {}, truncating",
+            max_candidates
+        );
+        ret.push_str(&format!(
+            "Candidate case count reached {}, truncating\n",
+            max_candidates,
+        ));
+
+        truncations.push(Truncation::CandidateLimit {
+            limit: max_candidates,
+            total: total_case_count,
+        });
+    }
+
+    info!(
+        "All combinations size: {}, answers size: {}, results size: {}",
+        processed_case_count,
+        answers_count,
+        result_equipments.len()
+    );
+
+    let elapsed_final = start_time.elapsed();
+
+    let all_answers_length = result_equipments
+        .iter()
+        .map(|result_equip| result_equip.deco_combs.len())
+        .sum::<usize>();
+
+    ret.push_str(&format!(
+        "calculate_skillset elapsed: {:?},\nanswers length: {}\n",
+        elapsed_final, all_answers_length
+    ));
+    info!("{}", ret);
+
+    monitor.report(SearchPhase::Done, all_answers_length, all_answers_length);
+
+    if let Some(sort_by) = &request.sort_by {
+        result_equipments.sort_by_key(|result_equip| Reverse(result_equip.stat.get(sort_by)));
+    }
+
+    let page_end = offset.saturating_add(max_results);
+
+    let next_cursor = if page_end < result_equipments.len() {
+        truncations.push(Truncation::ResultLimit {
+            limit: max_results,
+            offset,
+        });
+
+        Some(page_end)
+    } else {
+        None
+    };
+
+    let result_equipments = result_equipments
+        .into_iter()
+        .skip(offset)
+        .take(max_results)
+        .collect::<Vec<ResultFullEquipments>>();
+
+    Ok(Some(SearchResult {
+        log: ret,
+        full_equipments: result_equipments,
+        next_cursor,
+        truncations,
+    }))
+}
+
+/// Calls `f` with every index tuple within `sizes` whose indices sum up to `rank_sum`,
+/// stops and returns false once `f` returns false
+fn for_each_indices_by_rank_sum<F>(sizes: &Vec<usize>, rank_sum: usize, f: &mut F) -> bool
+where
+    F: FnMut(&Vec<usize>) -> bool,
+{
+    let mut indices = vec![0; sizes.len()];
+
+    fill_indices_by_rank_sum(sizes, 0, rank_sum, &mut indices, f)
+}
+
+fn fill_indices_by_rank_sum<F>(
+    sizes: &Vec<usize>,
+    part_index: usize,
+    rank_left: usize,
+    indices: &mut Vec<usize>,
+    f: &mut F,
+) -> bool
+where
+    F: FnMut(&Vec<usize>) -> bool,
+{
+    if part_index == sizes.len() - 1 {
+        if sizes[part_index] <= rank_left {
+            return true;
+        }
+
+        indices[part_index] = rank_left;
+
+        return f(indices);
+    }
+
+    let rest_max_rank = sizes[part_index + 1..]
+        .iter()
+        .map(|size| size - 1)
+        .sum::<usize>();
+
+    let min_index = rank_left.saturating_sub(rest_max_rank);
+    let max_index = (sizes[part_index] - 1).min(rank_left);
+
+    for index in min_index..max_index + 1 {
+        indices[part_index] = index;
+
+        if !fill_indices_by_rank_sum(sizes, part_index + 1, rank_left - index, indices, f) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// `None` if no decoration combination can be placed (within the owned decorations)
+fn to_result_full_equipments<'a>(
+    dm: &DataManager,
+    request: &SearchRequest,
+    equipments: Vec<BoxCalcEquipment<'a>>,
+    deco_combs: &[DecorationCombination],
+) -> Option<ResultFullEquipments> {
+    let result_armors = equipments
+        .iter()
+        .filter_map(|armor| {
+            if armor.part() == &ArmorPart::Talisman {
+                return None;
+            }
+
+            let armor = armor.as_armor();
+
+            let result_armor = ResultArmor {
+                id: armor.id().clone(),
+                base_id: armor.original_id().clone(),
+                is_anomaly: BaseArmor::is_anomaly_armor(armor.id()),
+                needs_crafting: !dm.armor_inventory.is_owned(armor.base()),
+                stat: armor.stat().clone(),
+                skills: armor.skills().clone(),
+                slots: armor.slots().clone(),
+            };
+
+            Some((armor.part().as_str().to_string(), result_armor))
+        })
+        .collect::<HashMap<String, ResultArmor>>();
+
+    let weapon_slots = &request.weapon_slots;
+
+    let result_deco_combs = deco_combs
+        .iter()
+        .filter_map(|deco_comb| {
+            let placement = place_decorations(
+                dm,
+                &request.selected_skills,
+                &request.free_slots,
+                weapon_slots,
+                &equipments,
+                deco_comb,
+                request.deco_inventory.as_ref(),
+            );
+
+            let placement = match placement {
+                Ok(placement) => placement,
+                Err(message) => {
+                    info!("Decoration combination without placement: {}", message);
+                    return None;
+                }
+            };
+
+            let missing_decos = match &request.deco_inventory {
+                Some(deco_inventory) => deco_inventory.get_missing(
+                    &placement
+                        .iter()
+                        .filter_map(|slot| slot.deco_id.as_ref())
+                        .collect(),
+                ),
+                None => HashMap::new(),
+            };
+
+            // Final check with the real armors, which might need fewer decorations
+            if !missing_decos.is_empty() && request.deco_inventory.as_ref().unwrap().limits_search()
+            {
+                return None;
+            }
+
+            Some(ResultDecorationCombination {
+                skills: deco_comb.combs_per_skill.clone(),
+                slots_sum: deco_comb.sum.clone(),
+                placement,
+                missing_decos,
+            })
+        })
+        .collect::<Vec<ResultDecorationCombination>>();
+
+    if result_deco_combs.is_empty() {
+        return None;
+    }
+
+    let total_stat = get_total_stat(&equipments);
+
+    let full_equip = FullEquipments::<'a>::new(weapon_slots.clone(), equipments);
+    let talisman = full_equip.get_by_part(&ArmorPart::Talisman).as_talisman(); // TODO: without FullEquipments
+
+    let result_tali = ResultTalisman {
+        id: talisman.id().clone(),
+        skills: talisman.skills().clone(),
+        slots: talisman.slots().clone(),
+    };
+
+    Some(ResultFullEquipments {
+        armors: result_armors,
+        deco_combs: result_deco_combs,
+        talisman: result_tali,
+        stat: total_stat,
+    })
+}
+
+/// Sends answers of candidates to the monitor as soon as they are found,
+/// before all candidates are sorted. Final results are still sorted.
+struct ResultStream<'m, 'f> {
+    monitor: &'m SearchMonitor<'f>,
+    /// Only the first page without stat sorting is streamed,
+    /// other pages depend on the order of all candidates
+    is_enabled: bool,
+    /// At most `max_results` candidates (a page worth) are streamed
+    streamed_ids: Mutex<HashSet<String>>,
+    /// Answers count and results of streamed candidates, reused by the final pass
+    streamed_results: Mutex<HashMap<String, (usize, Vec<ResultFullEquipments>)>>,
+}
+
+impl<'m, 'f> ResultStream<'m, 'f> {
+    fn new(monitor: &'m SearchMonitor<'f>, request: &SearchRequest) -> Self {
+        Self {
+            monitor,
+            is_enabled: monitor.streams_results()
+                && request.sort_by.is_none()
+                && request.cursor.unwrap_or(0) == 0,
+            streamed_ids: Mutex::new(HashSet::new()),
+            streamed_results: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn send_candidate<'a>(
+        &self,
+        dm: &'a DataManager,
+        request: &SearchRequest,
+        equipments: &Vec<&BoxCalcEquipment<'a>>,
+        req_skills: &HashMap<String, i32>,
+        avail_slots: &Vec<i32>,
+        full_equip_id: &str,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+
+        let has_possible_comb =
+            has_possible_deco_combs(dm, request.deco_inventory.as_ref(), req_skills, avail_slots);
+
+        if !has_possible_comb {
+            return;
+        }
+
+        {
+            let mut streamed_ids = self.streamed_ids.lock().unwrap();
+
+            if request.limits.max_results <= streamed_ids.len()
+                || !streamed_ids.insert(full_equip_id.to_string())
+            {
+                return;
+            }
+        }
+
+        let (answers_count, results) =
+            get_candidate_results(dm, request, req_skills, equipments, avail_slots);
+
+        for result in &results {
+            self.monitor.send_result(result);
+        }
+
+        self.streamed_results
+            .lock()
+            .unwrap()
+            .insert(full_equip_id.to_string(), (answers_count, results));
+    }
+
+    fn take_results(&self, full_equip_id: &str) -> Option<(usize, Vec<ResultFullEquipments>)> {
+        self.streamed_results.lock().unwrap().remove(full_equip_id)
+    }
+}
+
+/// Answers count of a candidate, and its results that can be placed (within the owned decorations)
+fn get_candidate_results<'a>(
+    dm: &'a DataManager,
+    request: &SearchRequest,
+    req_skills: &HashMap<String, i32>,
+    real_parts: &Vec<&BoxCalcEquipment<'a>>,
+    avail_slots: &Vec<i32>,
+) -> (usize, Vec<ResultFullEquipments>) {
+    let mut answers = Vec::new();
+
+    calculate_full_equip(
+        dm,
+        request,
+        req_skills,
+        real_parts,
+        avail_slots,
+        &mut answers,
+    );
+
+    let answers_count = answers.len();
+
+    let results = answers
+        .into_iter()
+        .filter_map(|(equipments, deco_combs)| {
+            to_result_full_equipments(dm, request, equipments, &deco_combs)
+        })
+        .collect::<Vec<ResultFullEquipments>>();
+
+    (answers_count, results)
+}
+
+fn calculate_full_equip<'a>(
+    dm: &'a DataManager,
+    request: &SearchRequest,
+    req_skills: &HashMap<String, i32>,
+    real_parts: &Vec<&BoxCalcEquipment<'a>>,
+    avail_slots: &Vec<i32>,
+    answers: &mut Vec<(Vec<BoxCalcEquipment<'a>>, Vec<DecorationCombination>)>,
+) {
+    let weapon_slots = &request.weapon_slots;
+    let sex_type = &request.sex_type;
+    let constraints = &request.constraints;
+    let lang = request.lang.as_str();
+
+    // Leftover skills might all be covered by single decos, which are already checked statically
+    let possible_deco_combs = if req_skills.is_empty() {
+        vec![DecorationCombination {
+            combs_per_skill: HashMap::new(),
+            sum: Vec::new(),
+        }]
+    } else {
+        let mut possible_deco_combs = dm.deco_combinations.get_possible_combs(req_skills);
+        possible_deco_combs.retain(|comb| {
+            comb.is_possible(avail_slots)
+                && match &request.deco_inventory {
+                    Some(deco_inventory) => deco_inventory.allows_comb(dm, req_skills, comb),
+                    None => true,
+                }
+        });
+
+        possible_deco_combs
+    };
+
+    if possible_deco_combs.is_empty() {
+        return;
+    }
+
+    let equipments = real_parts
+        .iter()
+        .map(|part| part.clone_dyn())
+        .collect::<Vec<BoxCalcEquipment<'a>>>();
+
+    let (all_skills, _) = FullEquipments::calculate_skills_slots(weapon_slots, &equipments);
+
+    debug!("Initial slots: {:?}", avail_slots);
+    debug!("Skill ids: {:?}", all_skills);
+
+    for local_answer in &possible_deco_combs {
+        debug!("Local answer: {:?}", local_answer);
+    }
+
+    debug!(
+        "Possible slot combinations: {:?} {:?}",
+        possible_deco_combs
+            .iter()
+            .map(|comb| &comb.combs_per_skill)
+            .collect::<Vec<&HashMap<String, Vec<i32>>>>(),
+        possible_deco_combs
+            .iter()
+            .map(|comb| &comb.sum)
+            .collect::<Vec<&Vec<i32>>>()
+    );
+
+    let mut equips_by_part = HashMap::new();
+
+    for equipment in real_parts {
+        equips_by_part.insert(equipment.part().clone(), equipment);
+    }
+
+    let helm = equips_by_part[&ArmorPart::Helm];
+    let torso = equips_by_part[&ArmorPart::Torso];
+    let arm = equips_by_part[&ArmorPart::Arm];
+    let waist = equips_by_part[&ArmorPart::Waist];
+    let feet = equips_by_part[&ArmorPart::Feet];
+    let tali = equips_by_part[&ArmorPart::Talisman];
+
+    debug!(
+        "Armors ids: ({}), ({}), ({}), ({}), ({}), ({})",
+        helm.id(),
+        torso.id(),
+        arm.id(),
+        waist.id(),
+        feet.id(),
+        tali.id(),
+    );
+
+    debug!(
+        "Armors names: ({}), ({}), ({}), ({}), ({})",
+        helm.as_armor().name(lang),
+        torso.as_armor().name(lang),
+        arm.as_armor().name(lang),
+        waist.as_armor().name(lang),
+        feet.as_armor().name(lang),
+    );
+
+    let mut real_armors = Vec::<Vec<BoxCalcEquipment<'a>>>::new();
+
+    for equipment in real_parts {
+        let equip_id = equipment.id();
+        let part = equipment.part();
+
+        let is_slot_equip = BaseArmor::is_slot_armor(equip_id);
+
+        if is_slot_equip {
+            if part == &ArmorPart::Talisman {
+                let talis_by_slot = dm.talismans_by_slot.get(equip_id).unwrap();
+
+                let mut all_real_talis = Vec::new();
+
+                for base_tali in talis_by_slot {
+                    if !constraints.allows_talisman(base_tali) {
+                        continue;
+                    }
+
+                    let box_tali = CalcTalisman::new(base_tali).clone_dyn();
+                    all_real_talis.push(box_tali);
+                }
+
+                real_armors.push(all_real_talis);
+            } else {
+                let armors_by_slot = &dm.armors_by_slot[part][equip_id];
+
+                let mut all_real_armors = Vec::<BoxCalcEquipment<'a>>::new();
+
+                for base_armor in armors_by_slot {
+                    // Anomaly armors are checked with their original armor, e.g. if it is excluded
+                    let (armor, original) = dm.get_armor_by_id(base_armor.id()).unwrap();
+
+                    if !armor.sex_type.is_wearable_by(sex_type)
+                        || !constraints.allows_armor(armor, original, &dm.armor_inventory)
+                    {
+                        continue;
+                    }
+
+                    let calc_armor = CalcArmor::<'a>::new_anomaly(armor, original);
+
+                    let box_armor = calc_armor.clone_dyn();
+
+                    all_real_armors.push(box_armor);
+                }
+
+                real_armors.push(all_real_armors);
+            }
+        } else {
+            real_armors.push(vec![equipment.clone_dyn()]);
+        }
+    }
+
+    let mut answers_equip = Vec::new();
+    let has_stat_limits = constraints.has_stat_limits();
+
+    for (a0, a1, a2, a3, a4, a5) in iproduct!(
+        &real_armors[0],
+        &real_armors[1],
+        &real_armors[2],
+        &real_armors[3],
+        &real_armors[4],
+        &real_armors[5]
+    ) {
+        let equipments = vec![
+            a0.clone(),
+            a1.clone(),
+            a2.clone(),
+            a3.clone(),
+            a4.clone(),
+            a5.clone(),
+        ];
+
+        if has_stat_limits && !constraints.allows_stat(&get_total_stat(&equipments)) {
+            continue;
+        }
+
+        answers_equip.push(equipments);
+    }
+
+    for equip in answers_equip.into_iter() {
+        answers.push((equip, possible_deco_combs.clone()));
+    }
+
+    info!("Answers length: {}\n", answers.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{search, SearchRequest, Truncation};
+    use crate::{
+        data::armor::{SexType, StatType},
+        feasibility::get_max_skill_levels,
+        test::{create_pinned_request, get_data_manager, get_equip_ids, load_data_manager},
+    };
+
+    #[test]
+    fn search_rejects_invalid_requests() {
+        let dm = get_data_manager();
+
+        let max_level = dm.skills["attack_boost"].max_level;
+
+        let create_request = |skill_id: &str, level: i32| {
+            let mut selected_skills = HashMap::new();
+            selected_skills.insert(skill_id.to_string(), level);
+
+            SearchRequest::new(
+                vec![0, 0, 0],
+                selected_skills,
+                vec![0, 0, 0, 0],
+                SexType::All,
+            )
+        };
+
+        let mut requests = vec![
+            create_request("attack_boost", 0),
+            create_request("attack_boost", max_level + 1),
+            create_request("not_existing", 1),
+        ];
+
+        let mut request = create_request("attack_boost", 1);
+        request.weapon_slots = vec![5, 0, 0];
+        requests.push(request);
+
+        let mut request = create_request("attack_boost", 1);
+        request.free_slots = vec![0, 0, 0];
+        requests.push(request);
+
+        for request in &requests {
+            assert!(search(dm, request).is_err(), "{:?}", request);
+            assert!(get_max_skill_levels(dm, request).is_err());
+        }
+
+        assert!(create_request("attack_boost", max_level)
+            .validate(dm)
+            .is_ok());
+    }
+
+    #[test]
+    fn search_is_deterministic() {
+        let request =
+            create_pinned_request(&[("critical_boost", 3), ("agitator", 3), ("attack_boost", 4)]);
+
+        // Each data manager has its own hash map ordering
+        let get_result_ids = || {
+            let dm = load_data_manager();
+
+            search(&dm, &request)
+                .unwrap()
+                .full_equipments
+                .iter()
+                .map(get_equip_ids)
+                .collect::<Vec<String>>()
+        };
+
+        let result_ids = get_result_ids();
+
+        assert_ne!(result_ids.len(), 0);
+        assert_eq!(result_ids, get_result_ids());
+    }
+
+    #[test]
+    fn search_pages_with_cursor() {
+        let dm = get_data_manager();
+
+        let mut request = create_pinned_request(&[("critical_boost", 3), ("agitator", 3)]);
+
+        request.limits.max_results = 100;
+
+        let both_pages = search(dm, &request).unwrap();
+
+        request.limits.max_results = 50;
+
+        let first_page = search(dm, &request).unwrap();
+
+        assert_eq!(first_page.full_equipments.len(), 50);
+        assert_eq!(first_page.next_cursor, Some(50));
+        assert_eq!(
+            first_page.truncations,
+            vec![Truncation::ResultLimit {
+                limit: 50,
+                offset: 0
+            }]
+        );
+
+        request.cursor = first_page.next_cursor;
+
+        let second_page = search(dm, &request).unwrap();
+
+        let paged_ids = first_page
+            .full_equipments
+            .iter()
+            .chain(second_page.full_equipments.iter())
+            .map(get_equip_ids)
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            paged_ids,
+            both_pages
+                .full_equipments
+                .iter()
+                .map(get_equip_ids)
+                .collect::<Vec<String>>()
+        );
+
+        request.limits.max_candidates = 1;
+        request.cursor = None;
+
+        let limited = search(dm, &request).unwrap();
+
+        assert!(limited
+            .truncations
+            .iter()
+            .any(|truncation| matches!(truncation, Truncation::CandidateLimit { limit: 1, .. })));
+
+        let total = limited
+            .truncations
+            .iter()
+            .find_map(|truncation| match truncation {
+                Truncation::CandidateLimit { total, .. } => Some(*total),
+                _ => None,
+            })
+            .unwrap();
+
+        assert!(3 < total);
+
+        // The page is full long before the limit, so no needed candidate is cut off
+        request.limits.max_candidates = total - 1;
+        request.limits.max_results = 1;
+
+        let first_result = search(dm, &request).unwrap();
+
+        assert_eq!(
+            first_result.truncations,
+            vec![Truncation::ResultLimit {
+                limit: 1,
+                offset: 0
+            }]
+        );
+
+        // Sorting by stat needs every candidate
+        request.sort_by = Some(StatType::Defense);
+
+        let sorted = search(dm, &request).unwrap();
+
+        assert!(sorted
+            .truncations
+            .iter()
+            .any(|truncation| matches!(truncation, Truncation::CandidateLimit { .. })));
+    }
+}
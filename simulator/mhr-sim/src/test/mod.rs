@@ -0,0 +1,122 @@
+use lazy_static::lazy_static;
+
+use crate::{
+    create_data_manager,
+    data::{
+        armor::{ArmorPart, SexType},
+        data_manager::DataManager,
+    },
+    search::{ResultFullEquipments, SearchRequest},
+};
+
+lazy_static! {
+    static ref DATA_MANAGER: DataManager = load_data_manager();
+}
+
+/// Loads the app data, for tests that change it or need their own hash map ordering
+pub(crate) fn load_data_manager() -> DataManager {
+    create_data_manager(
+        "../src-tauri/data/armor.json",
+        "../src-tauri/data/skill.json",
+        "../src-tauri/data/deco.json",
+    )
+    .unwrap()
+}
+
+/// App data loaded once and shared by the tests
+pub(crate) fn get_data_manager() -> &'static DataManager {
+    &DATA_MANAGER
+}
+
+/// Request with Silver Solhelm, Lambent Hauberk and Silver Solbraces pinned,
+/// which keeps the search small enough for tests
+pub(crate) fn create_pinned_request(selected_skills: &[(&str, i32)]) -> SearchRequest {
+    let selected_skills = selected_skills
+        .iter()
+        .map(|(id, level)| (id.to_string(), *level))
+        .collect();
+
+    let mut request = SearchRequest::new(
+        vec![3, 1, 0],
+        selected_skills,
+        vec![0, 0, 0, 0],
+        SexType::All,
+    );
+
+    for (part, id) in [
+        (ArmorPart::Helm, "silver_solhelm"),
+        (ArmorPart::Torso, "lambent_hauberk"),
+        (ArmorPart::Arm, "silver_solbraces"),
+    ] {
+        request
+            .constraints
+            .pinned_armors
+            .insert(part, id.to_string());
+    }
+
+    request
+}
+
+/// Armor and talisman ids of a result with its number of decoration combinations,
+/// to compare results across searches
+pub(crate) fn get_equip_ids(equip: &ResultFullEquipments) -> String {
+    let mut ids = ArmorPart::get_all()
+        .iter()
+        .filter_map(|part| equip.armors.get(part.as_str()))
+        .map(|armor| armor.id.clone())
+        .collect::<Vec<String>>();
+    ids.push(equip.talisman.id.clone());
+    ids.push(equip.deco_combs.len().to_string());
+
+    ids.join(",")
+}
+
+mod tests {
+    use std::collections::HashMap;
+
+    use log::info;
+
+    use super::get_data_manager;
+    use crate::{
+        data::{armor::SexType, deco_combination::DecorationCombination},
+        search::{search, SearchRequest},
+    };
+
+    #[test]
+    fn it_works() {
+        env_logger::init();
+
+        let dm = get_data_manager();
+
+        info!("Armors length: {}", dm.armors.len());
+
+        let mut selected_skills = HashMap::<String, i32>::new();
+        let weapon_slots = vec![3, 0, 0];
+        let free_slots = vec![0, 0, 0, 0];
+
+        selected_skills.insert("water_attack".to_string(), 5);
+        selected_skills.insert("element_exploit".to_string(), 1);
+
+        selected_skills.insert("bow_charge_plus".to_string(), 1);
+        selected_skills.insert("spread_up".to_string(), 3);
+
+        selected_skills.insert("weakness_exploit".to_string(), 3);
+        selected_skills.insert("chain_crit".to_string(), 1);
+
+        selected_skills.insert("constitution".to_string(), 5);
+        selected_skills.insert("stamina_surge".to_string(), 3);
+
+        let request =
+            SearchRequest::new(weapon_slots, selected_skills, free_slots, SexType::Female);
+
+        search(dm, &request).unwrap();
+    }
+
+    #[test]
+    fn deco_comb_compare1() {
+        assert!(DecorationCombination::is_possible_static(
+            &vec![3, 0, 2, 0],
+            &vec![3, 2, 0, 0]
+        ));
+    }
+}
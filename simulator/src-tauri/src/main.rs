@@ -3,1339 +3,621 @@
     windows_subsystem = "windows"
 )]
 
-pub static MAX_ANSWER_LENGTH: i32 = 200;
-
-use csv::StringRecord;
 use log::{debug, info};
-use std::cmp::Reverse;
-use std::collections::{BTreeMap, HashMap, HashSet};
-use std::fs::File;
-use std::io::BufReader;
-use std::sync::Mutex;
-use std::time::Instant;
-
-use data::armor::{ArmorPart, SexType};
-use data::data_manager::DataManager;
-use itertools::iproduct;
-use serde::{de, Serialize};
-use tauri::{CustomMenuItem, Manager, Menu, MenuItem, Submenu, WindowBuilder};
-
-mod data {
-    pub mod armor;
-    pub mod data_manager;
-    pub mod deco;
-    pub mod deco_combination;
-    pub mod skill;
-}
-
-mod calc {
-    pub mod armor;
-    pub mod calc_equipment;
-    pub mod deco;
-    pub mod talisman;
-}
-
-mod full_equipments;
-
-mod test;
-
-use crate::calc::armor::CalcArmor;
-use crate::calc::calc_equipment::CalcEquipment;
-use crate::calc::deco::CalcDeco;
-use crate::calc::talisman::CalcTalisman;
-use crate::data::armor::{
-    AnomalyArmor, ArmorSkill, ArmorStat, BaseArmor, Talisman, TalismanSkill, EMPTY_ARMOR_PREFIX,
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+
+use mhr_sim::data::armor::{AnomalyArmor, BaseArmor, StatType, Talisman, TalismanSkill};
+use mhr_sim::data::data_manager::DataManager;
+use mhr_sim::data::data_override::{OverrideFile, OVERRIDE_DIRNAME};
+use mhr_sim::data::data_validation::DataReport;
+use mhr_sim::data::name_dict::NameCollision;
+use mhr_sim::data::skill::Skill;
+use mhr_sim::full_equipments::FullEquipments;
+use mhr_sim::loadout::LOADOUTS_FILENAME;
+use mhr_sim::profile::PROFILE_FILENAME;
+use mhr_sim::search::ResultFullEquipments;
+use mhr_sim::{
+    create_data_manager_with_overrides, decode_build, encode_build, export_result_to_file,
+    get_max_skill_levels, parse_anomaly, parse_armor_inventory, parse_deco_inventory,
+    parse_talisman, search_additional_skills, search_with_monitor, AdditionalSkill,
+    AdditionalSkillsRequest, ArmorOwnership, CheckedLoadout, CsvImport, DecoInventory,
+    DecodedBuild, ExportFormat, HunterProfile, LoadoutStore, OwnedArmor, OwnedDeco,
+    SearchConstraints, SearchMonitor, SearchProgress, SearchRequest, SearchResult,
 };
-use crate::data::deco::Decoration;
-use crate::data::deco_combination::DecorationCombination;
-use crate::data::skill::Skill;
-use crate::full_equipments::FullEquipments;
-
-fn to_i32(record: &StringRecord, index: usize) -> i32 {
-    return record[index].parse().unwrap();
-}
-
-fn parse_data<T>(filename: &str) -> Vec<T>
-where
-    T: de::DeserializeOwned,
-{
-    let file = File::open(filename);
-
-    match file {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-
-            let values: Vec<T> = serde_json::from_reader(reader).unwrap_or(Vec::new());
-
-            values
-        }
-        Err(_) => Vec::new(),
-    }
-}
-
-fn parse_anomaly(
-    filename: &str,
-    armors: &HashMap<String, BaseArmor>,
-    armor_name_dict: &HashMap<String, String>,
-    skill_name_dict: &HashMap<String, String>,
-) -> Vec<AnomalyArmor> {
-    let file = File::open(filename);
-
-    match file {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-
-            let mut csv_reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .from_reader(reader);
-
-            let mut records = Vec::new();
-
-            for result in csv_reader.records() {
-                let record = result.unwrap();
-
-                records.push(record);
-            }
-
-            let mut anomaly_armors = Vec::new();
-
-            for record in records {
-                let armor_name = &record[0];
-
-                let defense = to_i32(&record, 1);
-
-                let fire_res = to_i32(&record, 2);
-                let water_res = to_i32(&record, 3);
-                let elec_res = to_i32(&record, 4);
-                let ice_res = to_i32(&record, 5);
-                let dragon_res = to_i32(&record, 6);
-
-                let slot_size1 = to_i32(&record, 7);
-                let slot_size2 = to_i32(&record, 8);
-                let slot_size3 = to_i32(&record, 9);
-
-                let slot_sizes = vec![slot_size1, slot_size2, slot_size3];
-
-                let stat = ArmorStat {
-                    defense,
-                    fire_res,
-                    water_res,
-                    elec_res,
-                    ice_res,
-                    dragon_res,
-                };
-
-                let mut anomaly_skills = HashMap::new();
-
-                for i in (10..record.len()).step_by(2) {
-                    let skill_name = &record[i];
-
-                    if skill_name == "" {
-                        continue;
-                    }
-
-                    let skill_level = to_i32(&record, i + 1);
-
-                    let skill_id = skill_name_dict.get(skill_name).unwrap();
-
-                    let anomaly_skill = ArmorSkill { level: skill_level };
-
-                    anomaly_skills.insert(skill_id.to_string(), anomaly_skill);
-                }
-
-                let armor_id = armor_name_dict.get(armor_name).unwrap();
-                let armor_info = armors.get(armor_id).unwrap();
-
-                let anomaly_armor =
-                    AnomalyArmor::new(armor_info.clone(), stat, slot_sizes, anomaly_skills);
-
-                anomaly_armors.push(anomaly_armor);
-            }
+use tauri::{AppHandle, CustomMenuItem, Manager, Menu, MenuItem, Submenu, WindowBuilder};
 
-            debug!("Anomaly parsed - count : {}", anomaly_armors.len());
+/// Search jobs keep a snapshot of the data for their whole run.
+/// Writes while a search runs copy the data instead of waiting for it.
+type SharedDataManager = RwLock<Arc<DataManager>>;
 
-            anomaly_armors
-        }
-        Err(_) => Vec::new(),
-    }
-}
-
-fn parse_talisman(filename: &str, skill_name_dict: &HashMap<String, String>) -> Vec<Talisman> {
-    let file = File::open(filename);
-
-    match file {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-
-            let mut csv_reader = csv::ReaderBuilder::new()
-                .has_headers(false)
-                .from_reader(reader);
-
-            let mut records = Vec::new();
-
-            for result in csv_reader.records() {
-                let record = result.unwrap();
-
-                records.push(record);
-            }
-
-            let mut talismans = Vec::new();
-
-            for (index, record) in records.iter().enumerate() {
-                let skill_name1 = &record[0];
-                let skill_level1 = to_i32(&record, 1);
-                let skill_name2 = &record[2];
-                let skill_level2 = to_i32(&record, 3);
-
-                let slot_size1 = to_i32(&record, 4);
-                let slot_size2 = to_i32(&record, 5);
-                let slot_size3 = to_i32(&record, 6);
-
-                let slot_sizes = vec![slot_size1, slot_size2, slot_size3];
-
-                let mut talisman_skills = Vec::new();
-
-                if skill_name1 != "" {
-                    let skill_id = skill_name_dict.get(skill_name1).unwrap();
-
-                    talisman_skills.push(TalismanSkill {
-                        id: skill_id.to_string(),
-                        level: skill_level1,
-                    });
-                }
-
-                if skill_name2 != "" {
-                    let skill_id = skill_name_dict.get(skill_name2).unwrap();
-
-                    talisman_skills.push(TalismanSkill {
-                        id: skill_id.to_string(),
-                        level: skill_level2,
-                    });
-                }
-
-                let talisman =
-                    Talisman::new(format!("talisman_{}", index), talisman_skills, slot_sizes);
-
-                talismans.push(talisman);
-            }
-
-            talismans
-        }
-        Err(_) => Vec::new(),
-    }
-}
+const SEARCH_PROGRESS_EVENT: &str = "search_progress";
+const SEARCH_RESULT_EVENT: &str = "search_result";
+const SEARCH_FINISHED_EVENT: &str = "search_finished";
 
 #[tauri::command]
 fn cmd_parse_anomaly(
     filename: &str,
-    mutex_dm: tauri::State<Mutex<DataManager>>,
-) -> Vec<AnomalyArmor> {
-    let mut dm = mutex_dm.lock().unwrap();
+    lang: Option<String>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<CsvImport<AnomalyArmor>, String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
 
     let anomalies = parse_anomaly(
         filename,
         &dm.armors,
         &dm.armor_name_dict,
         &dm.skill_name_dict,
-    );
+        lang.as_deref(),
+    )?;
 
-    dm.set_anomalies(anomalies.clone());
+    dm.set_anomalies(anomalies.values.clone());
 
-    return anomalies;
+    Ok(anomalies)
 }
 
 #[tauri::command]
-fn cmd_parse_talisman(filename: &str, mutex_dm: tauri::State<Mutex<DataManager>>) -> Vec<Talisman> {
-    let mut dm = mutex_dm.lock().unwrap();
+fn cmd_parse_talisman(
+    filename: &str,
+    lang: Option<String>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<CsvImport<Talisman>, String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
 
-    let talismans = parse_talisman(filename, &dm.skill_name_dict);
+    let talismans = parse_talisman(filename, &dm.skill_name_dict, lang.as_deref())?;
 
-    dm.set_talismans(talismans.clone());
+    dm.set_talismans(talismans.values);
 
-    return talismans;
+    // With the ids given by the data manager
+    Ok(CsvImport {
+        values: dm.talismans.clone(),
+        errors: talismans.errors,
+    })
 }
 
 #[tauri::command]
-fn cmd_get_skill_names(mutex_dm: tauri::State<Mutex<DataManager>>) -> HashMap<String, Skill> {
-    let dm = mutex_dm.lock().unwrap();
+fn cmd_get_talismans(rwlock_dm: tauri::State<SharedDataManager>) -> Vec<Talisman> {
+    let dm = rwlock_dm.read().unwrap();
 
-    return dm.skills.clone();
+    dm.talismans.clone()
 }
 
 #[tauri::command]
-fn cmd_get_armor_names(mutex_dm: tauri::State<Mutex<DataManager>>) -> HashMap<String, BaseArmor> {
-    let dm = mutex_dm.lock().unwrap();
-
-    return dm.armors.clone();
-}
-
-#[derive(Serialize)]
-struct CalculateSkillsetReturn {
-    log: String,
-    result: CalculateResult,
+fn cmd_add_talisman(
+    skills: Vec<TalismanSkill>,
+    slot_sizes: Vec<i32>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<Talisman, String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
+
+    dm.add_talisman(skills, slot_sizes)
 }
 
-#[derive(Serialize)]
-struct CalculateResult {
-    full_equipments: Vec<ResultFullEquipments>,
+#[tauri::command]
+fn cmd_edit_talisman(
+    talisman_id: String,
+    skills: Vec<TalismanSkill>,
+    slot_sizes: Vec<i32>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<Talisman, String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
+
+    dm.edit_talisman(&talisman_id, skills, slot_sizes)
 }
 
-#[derive(Serialize)]
-struct ResultFullEquipments {
-    pub armors: HashMap<String, ResultArmor>,
-    pub talisman: ResultTalisman,
-    pub deco_combs: Vec<ResultDecorationCombination>,
+#[tauri::command]
+fn cmd_delete_talisman(
+    talisman_id: String,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<(), String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
+
+    dm.delete_talisman(&talisman_id)
 }
 
-#[derive(Serialize)]
-struct ResultArmor {
-    pub base_id: String,
-    pub is_anomaly: bool,
+#[tauri::command]
+fn cmd_parse_armor_inventory(
+    filename: &str,
+    lang: Option<String>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<CsvImport<OwnedArmor>, String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
 
-    pub skills: HashMap<String, i32>,
-    pub slots: Vec<i32>,
-}
+    let owned_armors = parse_armor_inventory(filename, &dm.armor_name_dict, lang.as_deref())?;
 
-#[derive(Serialize)]
-struct ResultTalisman {
-    pub skills: HashMap<String, i32>,
-    pub slots: Vec<i32>,
-}
+    dm.set_armor_inventory(owned_armors.values.clone());
 
-#[derive(Serialize)]
-struct ResultDecorationCombination {
-    pub skills: HashMap<String, Vec<i32>>,
-    pub slots_sum: Vec<i32>,
+    Ok(owned_armors)
 }
 
-fn check_static_conditions<'a>(
-    dm: &DataManager,
-    weapon_slots: &Vec<i32>,
-    req_skills: &HashMap<String, i32>,
-    req_slots: &Vec<i32>,
-    equipments: &Vec<&BoxCalcEquipment<'a>>,
-) -> Option<(HashMap<String, i32>, Vec<i32>)> {
-    let mut req_skills = req_skills.clone();
-    let mut req_slots = req_slots.clone();
-
-    let real_parts = vec![
-        equipments[0].clone(),
-        equipments[1].clone(),
-        equipments[2].clone(),
-        equipments[3].clone(),
-        equipments[4].clone(),
-        equipments[5].clone(),
-    ];
-
-    let _real_ids = real_parts
-        .iter()
-        .map(|part| part.id().clone())
-        .collect::<HashSet<String>>();
-
-    let debug_case = vec![
-        "archfiend_armor_cerato",
-        "astalos_mail",
-        "rakna_armguards_x",
-        "silver_solcoil",
-        "storge_greaves",
-        dm.empty_talisman.id(),
-    ];
-
-    let _debug_case = debug_case
-        .iter()
-        .map(|id| id.to_string())
-        .collect::<HashSet<String>>();
-
-    let init_equip = FullEquipments::<'a>::new(weapon_slots.clone(), real_parts.clone());
-
-    for part in real_parts.iter() {
-        part.subtract_skills(&mut req_skills);
-    }
-
-    let is_debug_case = false;
+#[tauri::command]
+fn cmd_get_armor_inventory(
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> HashMap<String, ArmorOwnership> {
+    let dm = rwlock_dm.read().unwrap();
 
-    // let is_debug_case = _debug_case == _real_ids;
+    dm.armor_inventory.armors.clone()
+}
 
-    // if is_debug_case == false {
-    //     return None;
-    // } else {
-    //     debug!("Debug case reached");
-    // }
+/// `None` ownership removes the armor from the inventory
+#[tauri::command]
+fn cmd_set_armor_ownership(
+    armor_id: String,
+    ownership: Option<ArmorOwnership>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<(), String> {
+    let mut shared_dm = rwlock_dm.write().unwrap();
+    let dm = Arc::make_mut(&mut shared_dm);
+
+    dm.set_armor_ownership(&armor_id, ownership)
+}
 
-    let (no_deco_skills, single_deco_skills, _) = dm.get_skils_by_deco(&req_skills);
+/// Owned decorations are kept by the frontend and sent with each search
+#[tauri::command]
+fn cmd_parse_deco_inventory(
+    filename: &str,
+    lang: Option<String>,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<CsvImport<OwnedDeco>, String> {
+    let dm = rwlock_dm.read().unwrap();
 
-    if no_deco_skills.len() != 0 {
-        panic!("This shouldn't happen");
-    }
+    parse_deco_inventory(filename, &dm.deco_name_dict, lang.as_deref())
+}
 
-    let single_deco_skills = single_deco_skills
-        .iter()
-        .map(|(id, (slot_size, count))| (id, *slot_size, *count))
-        .collect::<Vec<(&String, i32, i32)>>();
+/// Names that can't be imported without the csv language
+#[tauri::command]
+fn cmd_get_name_collisions(rwlock_dm: tauri::State<SharedDataManager>) -> Vec<NameCollision> {
+    let dm = rwlock_dm.read().unwrap();
 
-    let single_decos_as_slots = CalcDeco::convert_to_slots(&single_deco_skills);
+    dm.get_name_collisions()
+}
 
-    for (slot_size_index, count) in single_decos_as_slots.iter().enumerate() {
-        req_slots[slot_size_index] += count;
-    }
+/// Warnings of the loaded data, e.g. skipped overrides
+#[tauri::command]
+fn cmd_get_data_report(report: tauri::State<DataReport>) -> DataReport {
+    (*report).clone()
+}
 
-    let (_, mut avail_slots) = FullEquipments::calculate_skills_slots(&weapon_slots, &real_parts);
+#[tauri::command]
+fn cmd_get_skill_names(rwlock_dm: tauri::State<SharedDataManager>) -> HashMap<String, Skill> {
+    let dm = rwlock_dm.read().unwrap();
 
-    let slot_success =
-        DecorationCombination::is_possible_static_mut(&mut avail_slots, &mut req_slots);
+    dm.skills.clone()
+}
 
-    if slot_success == false {
-        if is_debug_case {
-            debug!(
-                "Debug slots: {:?}, {:?}, {:?}",
-                single_deco_skills, init_equip.avail_slots, req_slots
-            );
-        }
+#[tauri::command]
+fn cmd_get_armor_names(rwlock_dm: tauri::State<SharedDataManager>) -> HashMap<String, BaseArmor> {
+    let dm = rwlock_dm.read().unwrap();
 
-        return None;
-    }
+    dm.armors.clone()
+}
 
-    for (id, _, _) in &single_deco_skills {
-        req_skills.remove(*id);
-    }
+struct ProfileState {
+    filename: PathBuf,
+    profile: HunterProfile,
+}
 
-    // This only calculates the number of slots regardless of slot size, just for candidate optimization
-    let mut minimum_slot_sum = 0;
+#[tauri::command]
+fn cmd_get_profile(mutex_profile: tauri::State<Mutex<ProfileState>>) -> HunterProfile {
+    let profile_state = mutex_profile.lock().unwrap();
 
-    for (skill_id, &level) in &req_skills {
-        let mut deco_sum_per_level = dm
-            .deco_combinations
-            .get(skill_id)
-            .unwrap()
-            .get(level as usize - 1)
-            .unwrap()
-            .iter()
-            .map(|comb| comb.iter().sum::<i32>())
-            .collect::<Vec<i32>>();
-
-        deco_sum_per_level.sort();
-
-        if is_debug_case {
-            debug!(
-                "Slot combs per skill: id: {}, level: {}, combs: {:?}",
-                skill_id,
-                level,
-                dm.deco_combinations
-                    .get(skill_id)
-                    .unwrap()
-                    .get(level as usize - 1)
-                    .unwrap()
-            );
-        }
+    profile_state.profile.clone()
+}
 
-        minimum_slot_sum += deco_sum_per_level[0];
-    }
+#[tauri::command]
+fn cmd_set_profile(
+    profile: HunterProfile,
+    mutex_profile: tauri::State<Mutex<ProfileState>>,
+) -> Result<HunterProfile, String> {
+    let mut profile_state = mutex_profile.lock().unwrap();
 
-    let equip_slot_sum = avail_slots.iter().sum::<i32>();
+    profile
+        .save(&profile_state.filename)
+        .map_err(|err| format!("Failed to save profile: {}", err))?;
 
-    if equip_slot_sum < minimum_slot_sum {
-        if is_debug_case {
-            debug!(
-                "Debug slots: {} < {}, avail_slots: {:?}, req_skills: {:?}",
-                equip_slot_sum, minimum_slot_sum, avail_slots, req_skills
-            );
-        }
+    profile_state.profile = profile.clone();
 
-        return None;
-    }
+    Ok(profile)
+}
 
-    Some((req_skills, avail_slots))
+struct LoadoutState {
+    filename: PathBuf,
+    store: LoadoutStore,
 }
 
 #[tauri::command]
-fn cmd_calculate_skillset(
+fn cmd_save_loadout(
+    name: String,
+    notes: String,
+    tags: Vec<String>,
     weapon_slots: Vec<i32>,
-    selected_skills: HashMap<String, i32>,
-    free_slots: Vec<i32>,
-    mutex_dm: tauri::State<Mutex<DataManager>>,
-) -> CalculateSkillsetReturn {
-    debug!("Start calculating...");
-
-    let dm = mutex_dm.lock().unwrap();
-
-    // TODO get sex_type as input
-    let (log, result) = calculate_skillset(
-        weapon_slots,
-        selected_skills,
-        free_slots,
-        SexType::Female,
-        &dm,
-    );
-
-    CalculateSkillsetReturn { log, result }
+    equipments: ResultFullEquipments,
+    mutex_loadouts: tauri::State<Mutex<LoadoutState>>,
+) -> Result<u32, String> {
+    let mut loadout_state = mutex_loadouts.lock().unwrap();
+
+    let id = loadout_state
+        .store
+        .add(name, notes, tags, weapon_slots, equipments)
+        .id;
+
+    loadout_state
+        .store
+        .save(&loadout_state.filename)
+        .map_err(|err| format!("Failed to save loadouts: {}", err))?;
+
+    Ok(id)
 }
 
-type BoxCalcEquipment<'a> = Box<dyn CalcEquipment<'a> + 'a>;
-
-fn calculate_skillset<'a>(
-    weapon_slots: Vec<i32>,
-    selected_skills: HashMap<String, i32>,
-    free_slots: Vec<i32>,
-    sex_type: SexType,
-    dm: &'a DataManager,
-) -> (String, CalculateResult) {
-    let start_time = Instant::now();
-    let mut ret = String::from("\n");
-
-    let mut decos_possible = HashMap::<String, Vec<&Decoration>>::new();
+/// Saved loadouts, flagged as broken if their anomaly armors or talismans changed since
+#[tauri::command]
+fn cmd_get_loadouts(
+    rwlock_dm: tauri::State<SharedDataManager>,
+    mutex_loadouts: tauri::State<Mutex<LoadoutState>>,
+) -> Vec<CheckedLoadout> {
+    let dm = rwlock_dm.read().unwrap();
 
-    let (no_deco_skills, yes_deco_skills) = dm.get_leftover_skills(&selected_skills);
+    mutex_loadouts.lock().unwrap().store.get_checked(&dm)
+}
 
-    for (skill_id, _) in &selected_skills {
-        let decos = dm.get_deco_by_skill_id(skill_id);
+#[tauri::command]
+fn cmd_delete_loadout(
+    id: u32,
+    mutex_loadouts: tauri::State<Mutex<LoadoutState>>,
+) -> Result<bool, String> {
+    let mut loadout_state = mutex_loadouts.lock().unwrap();
 
-        if 0 < decos.len() {
-            decos_possible.insert(skill_id.clone(), decos);
-        }
+    if !loadout_state.store.delete(id) {
+        return Ok(false);
     }
 
-    let decos_possible = decos_possible;
-
-    info!("Skills with yes deco: {:?}", yes_deco_skills);
-    info!("Skills with no deco: {:?}", no_deco_skills);
-
-    let helms = dm.get_parts(ArmorPart::Helm);
-    let torsos = dm.get_parts(ArmorPart::Torso);
-    let arms = dm.get_parts(ArmorPart::Arm);
-    let waists = dm.get_parts(ArmorPart::Waist);
-    let feets = dm.get_parts(ArmorPart::Feet);
-
-    let mut base_armors = HashMap::<ArmorPart, Vec<&BaseArmor>>::new();
+    loadout_state
+        .store
+        .save(&loadout_state.filename)
+        .map_err(|err| format!("Failed to save loadouts: {}", err))?;
 
-    base_armors.insert(ArmorPart::Helm, helms);
-    base_armors.insert(ArmorPart::Torso, torsos);
-    base_armors.insert(ArmorPart::Arm, arms);
-    base_armors.insert(ArmorPart::Waist, waists);
-    base_armors.insert(ArmorPart::Feet, feets);
-
-    let mut all_armors = HashMap::<ArmorPart, Vec<CalcArmor<'a>>>::new();
-    let mut armors_count_before = 0;
-
-    for (part, base_armors) in base_armors.iter() {
-        let mut calc_armors = Vec::new();
-
-        for base_armor in base_armors {
-            let anomaly_base = dm.get_anomaly_armor(base_armor.id());
-
-            let calc_armor = match anomaly_base {
-                Some(anomaly_armor) => {
-                    CalcArmor::<'a>::new_anomaly(&anomaly_armor.affected, base_armor)
-                }
-                None => CalcArmor::<'a>::new_anomaly(base_armor, base_armor),
-            };
-
-            calc_armors.push(calc_armor);
-            armors_count_before += 1;
-        }
+    Ok(true)
+}
 
-        all_armors.insert(part.clone(), calc_armors);
-    }
+/// Search input of the app, the rest of the request comes from the profile
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalculateInput {
+    weapon_slots: Option<Vec<i32>>,
+    selected_skills: HashMap<String, i32>,
+    free_slots: Vec<i32>,
+    constraints: Option<SearchConstraints>,
+    sort_by: Option<StatType>,
+    cursor: Option<usize>,
+    deco_inventory: Option<DecoInventory>,
+}
 
-    for (_, armors) in all_armors.iter_mut() {
-        *armors = armors
-            .iter_mut()
-            .filter_map(|armor| {
-                if armor.sex_type() == &sex_type || armor.sex_type() == &SexType::All {
-                    return Some(armor.clone());
-                } else {
-                    return None;
-                }
-            })
-            .collect::<Vec<CalcArmor<'a>>>();
+fn create_calculate_request(input: CalculateInput, profile: &HunterProfile) -> SearchRequest {
+    let mut request =
+        profile.create_request(input.weapon_slots, input.selected_skills, input.free_slots);
 
-        armors.sort_by_key(|armor| {
-            armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills)
-        });
+    if let Some(constraints) = input.constraints {
+        request.constraints = constraints;
     }
 
-    let all_talismans = dm
-        .talismans
-        .iter()
-        .map(|tali| CalcTalisman::<'a>::new(tali))
-        .collect::<Vec<CalcTalisman<'a>>>();
-
-    let talisman_count_before = all_talismans.len();
-
-    let mut temp_armors = Vec::new();
-    let mut temp_talismans = Vec::new();
+    request.sort_by = input.sort_by;
+    request.cursor = input.cursor;
+    request.deco_inventory = input.deco_inventory;
 
-    for (part, part_armors) in &all_armors {
-        for (i1, armor1) in part_armors.iter().enumerate() {
-            let mut is_le = false;
-
-            for i2 in i1 + 1..all_armors.len() {
-                let armor2 = &part_armors[i2];
-
-                if armor1.is_le(armor2) {
-                    is_le = true;
-                    break;
-                }
-            }
+    request
+}
 
-            if is_le == false {
-                temp_armors.push((part, armor1));
-            }
-        }
-    }
+/// Writes a result the webview got back to a file, with names in the profile's language
+#[tauri::command]
+fn cmd_export_result(
+    result: SearchResult,
+    format: ExportFormat,
+    filename: String,
+    rwlock_dm: tauri::State<SharedDataManager>,
+    mutex_profile: tauri::State<Mutex<ProfileState>>,
+) -> Result<(), String> {
+    let lang = mutex_profile.lock().unwrap().profile.lang.clone();
+
+    let dm = rwlock_dm.read().unwrap();
+
+    export_result_to_file(&dm, &result, &lang, format, &PathBuf::from(filename))
+}
 
-    for (i1, tali1) in all_talismans.iter().enumerate() {
-        let mut is_le = false;
+/// Shareable code of a result with the decorations of one of its combinations
+#[tauri::command]
+fn cmd_encode_build(
+    weapon_slots: Vec<i32>,
+    equipments: ResultFullEquipments,
+    deco_comb_index: usize,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<String, String> {
+    let dm = rwlock_dm.read().unwrap();
 
-        for i2 in i1 + 1..all_talismans.len() {
-            let tali2 = &all_talismans[i2];
+    let deco_comb = match equipments.deco_combs.get(deco_comb_index) {
+        Some(deco_comb) => deco_comb,
+        None => return Err(format!("No decoration combination {}", deco_comb_index)),
+    };
 
-            if tali1.is_le(tali2) {
-                is_le = true;
-                break;
-            }
-        }
+    let full_equipments = FullEquipments::from_result(&dm, weapon_slots, &equipments)?;
 
-        if is_le == false {
-            temp_talismans.push(tali1);
-        }
-    }
+    encode_build(&dm, &full_equipments, &deco_comb.placement)
+}
 
-    // TODO: save inferior armors/talismans later in order to give choices to choose for defense stats
-    // let mut all_armors = HashMap::<ArmorPart, Vec<CalcArmor>>::new();
-    // let mut armors_count_after = 0;
+#[tauri::command]
+fn cmd_decode_build(
+    code: String,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<DecodedBuild, String> {
+    let dm = rwlock_dm.read().unwrap();
 
-    // for (part, armor) in temp_armors {
-    //     let mut existing = all_armors.get_mut(&part);
+    decode_build(&dm, &code)
+}
 
-    //     if existing.is_none() {
-    //         all_armors.insert(part.clone(), Vec::<CalcArmor>::new());
-    //         existing = all_armors.get_mut(&part);
-    //     }
+#[derive(Default)]
+struct SearchJobs {
+    last_id: u32,
+    cancel_flags: HashMap<u32, Arc<AtomicBool>>,
+}
 
-    //     existing.unwrap().push(armor.clone());
-    //     armors_count_after += 1;
-    // }
+#[derive(Serialize, Clone)]
+struct SearchProgressPayload {
+    #[serde(rename = "jobId")]
+    job_id: u32,
+    progress: SearchProgress,
+}
 
-    // let all_talismans = temp_talismans
-    //     .iter_mut()
-    //     .map(|tali| tali.clone())
-    //     .collect::<Vec<CalcTalisman>>();
+#[derive(Serialize, Clone)]
+struct SearchResultPayload {
+    #[serde(rename = "jobId")]
+    job_id: u32,
+    equipments: ResultFullEquipments,
+}
 
-    // let talisman_count_after = all_talismans.len();
+#[derive(Serialize, Clone)]
+struct SearchFinishedPayload {
+    #[serde(rename = "jobId")]
+    job_id: u32,
+    cancelled: bool,
+    result: Option<SearchResult>,
+    error: Option<String>,
+}
 
-    // info!(
-    //     "Armors count before & after: {} -> {}",
-    //     armors_count_before, armors_count_after
-    // );
+/// Runs the search in a background thread, emitting progress and results as events.
+/// Full equipments are emitted as soon as found, the finished event has the sorted result
+/// or the error of the search. Invalid requests fail before a job starts.
+fn start_search_job(app: AppHandle, request: SearchRequest) -> Result<u32, String> {
+    // Imports and edits made meanwhile don't wait for the search, nor change its data
+    let dm = app.state::<SharedDataManager>().read().unwrap().clone();
 
-    // info!(
-    //     "Talisman count before & after: {} -> {}",
-    //     talisman_count_before, talisman_count_after
-    // );
+    request.validate(&dm)?;
 
-    let mut all_slot_equips = HashMap::<ArmorPart, HashMap<String, BoxCalcEquipment<'a>>>::new();
+    let cancel_flag = Arc::new(AtomicBool::new(false));
 
-    for (part, _) in all_armors.iter() {
-        let slot_only_armors = dm.slot_only_armors.get(part).unwrap();
-        let mut part_slot_armors = HashMap::<String, BoxCalcEquipment<'a>>::new();
+    let job_id = {
+        let mutex_jobs = app.state::<Mutex<SearchJobs>>();
+        let mut jobs = mutex_jobs.lock().unwrap();
 
-        for (id, armor) in slot_only_armors {
-            let calc_armor = CalcArmor::<'a>::new(armor).clone_dyn();
+        jobs.last_id += 1;
 
-            part_slot_armors.insert(id.clone(), calc_armor);
-        }
+        let job_id = jobs.last_id;
+        jobs.cancel_flags.insert(job_id, cancel_flag.clone());
 
-        all_slot_equips.insert(part.clone(), part_slot_armors);
-    }
+        job_id
+    };
 
-    all_slot_equips.insert(
-        ArmorPart::Talisman,
-        dm.slot_only_talismans
-            .iter()
-            .map(|(id, tali)| (id.clone(), CalcTalisman::new(tali).clone_dyn()))
-            .collect::<HashMap<String, BoxCalcEquipment<'a>>>(),
-    );
+    thread::spawn(move || {
+        debug!("Search job {} started", job_id);
 
-    let mut equips_with_deco_skills = HashMap::<&ArmorPart, Vec<BoxCalcEquipment<'a>>>::new();
+        let progress_app = app.clone();
+        let result_app = app.clone();
 
-    for (part, armors) in &all_armors {
-        let mut part_armors = Vec::<BoxCalcEquipment<'a>>::new();
+        let monitor = SearchMonitor::new(cancel_flag, move |progress| {
+            let payload = SearchProgressPayload {
+                job_id,
+                progress: progress.clone(),
+            };
 
-        for armor in armors {
-            for (skill_id, _) in &decos_possible {
-                if armor.skills().contains_key(skill_id) {
-                    part_armors.push(armor.clone_dyn());
-                    break;
-                }
+            if let Err(err) = progress_app.emit_all(SEARCH_PROGRESS_EVENT, payload) {
+                debug!("Failed to emit search progress: {}", err);
             }
-        }
-
-        equips_with_deco_skills.insert(part, part_armors);
-    }
-
-    let mut tali_with_deco_skills = Vec::new();
+        })
+        .with_result_listener(move |equipments| {
+            let payload = SearchResultPayload {
+                job_id,
+                equipments: equipments.clone(),
+            };
 
-    for tali in &all_talismans {
-        for (skill_id, _) in &decos_possible {
-            if tali.skills().contains_key(skill_id) {
-                tali_with_deco_skills.push(tali.clone_dyn());
-                break;
+            if let Err(err) = result_app.emit_all(SEARCH_RESULT_EVENT, payload) {
+                debug!("Failed to emit search result: {}", err);
             }
-        }
-    }
-
-    equips_with_deco_skills.insert(&ArmorPart::Talisman, tali_with_deco_skills);
-
-    let mut mr_armors = HashMap::<&ArmorPart, Vec<CalcArmor<'a>>>::new();
-
-    for (part, armors) in &all_armors {
-        mr_armors.insert(
-            part,
-            armors
-                .iter()
-                .filter_map(|armor| {
-                    if 7 <= armor.rarity() {
-                        return Some(armor.clone());
-                    } else {
-                        return None;
-                    }
-                })
-                .collect(),
-        );
-    }
-
-    let mut all_unique_armors = HashMap::<&ArmorPart, Vec<BoxCalcEquipment<'a>>>::new();
-
-    for (part, armors) in &all_armors {
-        all_unique_armors.insert(
-            part,
-            armors
-                .iter()
-                .filter_map(|armor| {
-                    for (skill_id, _) in &no_deco_skills {
-                        if armor.skills().contains_key(skill_id) {
-                            return Some(armor.clone_dyn());
-                        }
-                    }
+        });
 
-                    None
-                })
-                .collect::<Vec<BoxCalcEquipment<'a>>>(),
-        );
+        let (result, error) = match search_with_monitor(&dm, &request, &monitor) {
+            Ok(result) => (result, None),
+            Err(message) => (None, Some(message)),
+        };
 
-        all_unique_armors
-            .get_mut(part)
+        app.state::<Mutex<SearchJobs>>()
+            .lock()
             .unwrap()
-            .push(CalcArmor::<'a>::new(dm.empty_armors.get(&part).unwrap()).clone_dyn());
-    }
-
-    all_unique_armors.insert(
-        &ArmorPart::Talisman,
-        all_talismans
-            .iter()
-            .filter_map(|tali| {
-                for (skill_id, _) in &no_deco_skills {
-                    if tali.skills().contains_key(skill_id) {
-                        return Some(tali.clone_dyn());
-                    }
-                }
-
-                None
-            })
-            .collect(),
-    );
+            .cancel_flags
+            .remove(&job_id);
 
-    all_unique_armors
-        .get_mut(&ArmorPart::Talisman)
-        .unwrap()
-        .push(CalcTalisman::new(&dm.empty_talisman).clone_dyn());
-
-    for (_, unique_armors) in all_unique_armors.iter_mut() {
-        unique_armors.sort_by_key(|armor| {
-            armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills)
-        });
-    }
-
-    let mut possible_unique_equips = Vec::<Vec<BoxCalcEquipment<'a>>>::new();
-
-    for (helm, torso, arm, waist, feet, tali) in iproduct!(
-        all_unique_armors[&ArmorPart::Helm].iter(),
-        all_unique_armors[&ArmorPart::Torso].iter(),
-        all_unique_armors[&ArmorPart::Arm].iter(),
-        all_unique_armors[&ArmorPart::Waist].iter(),
-        all_unique_armors[&ArmorPart::Feet].iter(),
-        all_unique_armors[&ArmorPart::Talisman].iter()
-    ) {
-        let equips: Vec<BoxCalcEquipment<'a>> = vec![
-            helm.clone_dyn(),
-            torso.clone_dyn(),
-            arm.clone_dyn(),
-            waist.clone_dyn(),
-            feet.clone_dyn(),
-            tali.clone_dyn(),
-        ];
-
-        let full_equip = FullEquipments::<'a>::new(weapon_slots.clone(), equips.clone());
-        let possible_result = full_equip.contains_skills(&no_deco_skills.clone());
-
-        if possible_result == false {
-            continue;
-        }
+        let cancelled = result.is_none() && error.is_none();
 
         debug!(
-            "Unique skills possible: {:?}",
-            vec![
-                helm.id(),
-                torso.id(),
-                arm.id(),
-                waist.id(),
-                feet.id(),
-                tali.id()
-            ]
+            "Search job {} finished, cancelled: {}, error: {:?}",
+            job_id, cancelled, error
         );
 
-        possible_unique_equips.push(equips);
-    }
+        let payload = SearchFinishedPayload {
+            job_id,
+            cancelled,
+            result,
+            error,
+        };
 
-    possible_unique_equips.sort_by_key(|armors| {
-        let mut sum = 0;
-
-        for armor in armors {
-            sum += armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills);
+        if let Err(err) = app.emit_all(SEARCH_FINISHED_EVENT, payload) {
+            debug!("Failed to emit search finished: {}", err);
         }
-
-        Reverse(sum)
     });
 
-    ret.push_str(&format!(
-        "Unique armors count: {}, calculation: {:?}\n",
-        possible_unique_equips.len(),
-        start_time.elapsed()
-    ));
-
-    info!(
-        "Unique armors count: {}, calculation: {:?}",
-        possible_unique_equips.len(),
-        start_time.elapsed()
-    );
-
-    let mut all_calculate_cases = HashMap::new();
-    let mut all_parts_before_len = 0;
-
-    for possible_unique_vec in &possible_unique_equips {
-        let mut parts = possible_unique_vec
-            .iter()
-            .map(|equipment| {
-                let part = equipment.part();
-
-                let mut ret = Vec::<&BoxCalcEquipment<'a>>::new();
-
-                if equipment.id().starts_with(EMPTY_ARMOR_PREFIX) {
-                    let part_unique_armors = &all_unique_armors[part]
-                        .iter()
-                        .map(|armor| armor)
-                        .collect::<Vec<&BoxCalcEquipment<'a>>>();
-
-                    let part_deco_armors = &equips_with_deco_skills[part]
-                        .iter()
-                        .map(|armor| armor)
-                        .collect::<Vec<&BoxCalcEquipment<'a>>>();
-
-                    let part_slot_armors = &all_slot_equips[part]
-                        .iter()
-                        .filter_map(|(_, armor)| {
-                            for unique_armor in part_unique_armors.iter() {
-                                if &BaseArmor::get_slot_armor_id(unique_armor.slots()) == armor.id()
-                                {
-                                    return None;
-                                }
-                            }
-
-                            for deco_armor in part_deco_armors.iter() {
-                                if &BaseArmor::get_slot_armor_id(deco_armor.slots()) == armor.id() {
-                                    return None;
-                                }
-                            }
-
-                            return Some(armor);
-                        })
-                        .collect::<Vec<&BoxCalcEquipment<'a>>>();
-
-                    for armor in part_unique_armors.iter() {
-                        ret.push(armor);
-                    }
-
-                    for armor in part_deco_armors.iter() {
-                        ret.push(armor);
-                    }
-
-                    for armor in part_slot_armors.iter() {
-                        ret.push(armor);
-                    }
-                } else {
-                    ret.push(equipment);
-                }
-
-                ret.sort_by_key(|armor| {
-                    armor.get_point(&decos_possible, &yes_deco_skills, &no_deco_skills)
-                });
-
-                ret
-            })
-            .collect::<Vec<Vec<&BoxCalcEquipment<'a>>>>();
-
-        parts.sort_by_key(|parts| parts.len());
-
-        let mut total_count = 1;
-
-        for part_armors in &parts {
-            total_count *= part_armors.len();
-        }
-
-        debug!(
-            "Parts sorted id: {} {} {} {} {} {}",
-            possible_unique_vec[0].id(),
-            possible_unique_vec[1].id(),
-            possible_unique_vec[2].id(),
-            possible_unique_vec[3].id(),
-            possible_unique_vec[4].id(),
-            possible_unique_vec[5].id(),
-        );
-
-        debug!(
-            "Parts size: {} {} {} {} {} {}, total: {}",
-            parts[0].len(),
-            parts[1].len(),
-            parts[2].len(),
-            parts[3].len(),
-            parts[4].len(),
-            parts[5].len(),
-            total_count,
-        );
-
-        // Check for static conditions
-        for (&p0, &p1, &p2, &p3, &p4, &p5) in
-            iproduct!(&parts[0], &parts[1], &parts[2], &parts[3], &parts[4], &parts[5])
-        {
-            all_parts_before_len += 1;
-
-            let equipments = vec![p0, p1, p2, p3, p4, p5];
-
-            let full_equip_id = FullEquipments::get_full_equip_id(&equipments);
-
-            if all_calculate_cases.contains_key(&full_equip_id) {
-                continue;
-            }
-
-            let multi_deco_leftovers = check_static_conditions(
-                dm,
-                &weapon_slots,
-                &selected_skills,
-                &free_slots,
-                &equipments,
-            );
-
-            if multi_deco_leftovers.is_none() {
-                continue;
-            }
-
-            let (multi_deco_req_skills, avail_slots) = multi_deco_leftovers.unwrap();
-
-            all_calculate_cases.insert(
-                full_equip_id,
-                (equipments, multi_deco_req_skills, avail_slots),
-            );
-        }
-    }
-
-    ret.push_str(&format!(
-        "Empty armors expand: before count: {}, after count: {}, time: {:?}\n",
-        all_parts_before_len,
-        all_calculate_cases.len(),
-        start_time.elapsed()
-    ));
-
-    info!(
-        "Empty armors expand: before count: {}, after count: {}, time: {:?}\n",
-        all_parts_before_len,
-        all_calculate_cases.len(),
-        start_time.elapsed()
-    );
-
-    let mut all_loop_tree = BTreeMap::new();
-    let mut total_case_count = 0;
-
-    for (_, (equipments, multi_deco_req_skills, avail_slots)) in &all_calculate_cases {
-        let has_possible_comb = dm
-            .deco_combinations
-            .has_possible_combs(&multi_deco_req_skills, &avail_slots);
-
-        if has_possible_comb == false {
-            continue;
-        }
-
-        debug!(
-            "Possible candidiate: {:?}\nleft skills: {:?}, slots: {:?}",
-            equipments
-                .iter()
-                .map(|part| part.id())
-                .collect::<Vec<&String>>(),
-            multi_deco_req_skills,
-            avail_slots
-        );
-
-        let total_point = CalcDeco::get_point(&avail_slots);
-
-        let mut existing = all_loop_tree.get_mut(&Reverse(total_point));
-
-        if existing.is_none() {
-            all_loop_tree.insert(
-                Reverse(total_point),
-                Vec::<(Vec<&BoxCalcEquipment<'a>>, Vec<i32>, HashMap<String, i32>)>::new(),
-            );
-            existing = all_loop_tree.get_mut(&Reverse(total_point));
-        }
-
-        existing.unwrap().push((
-            equipments.clone(),
-            avail_slots.clone(),
-            multi_deco_req_skills.clone(),
-        ));
-
-        total_case_count += 1;
-
-        if MAX_ANSWER_LENGTH <= total_case_count {
-            debug!(
-                "Candidate case count reached {}, breaking",
-                MAX_ANSWER_LENGTH
-            );
-            ret.push_str(&format!(
-                "Candidate case count reached {}, breaking\n",
-                MAX_ANSWER_LENGTH,
-            ));
-            break;
-        }
-    }
+    Ok(job_id)
+}
 
-    let elapsed_sort = start_time.elapsed();
+#[tauri::command]
+fn cmd_start_calculate_skillset(
+    input: CalculateInput,
+    app_handle: AppHandle,
+    mutex_profile: tauri::State<Mutex<ProfileState>>,
+) -> Result<u32, String> {
+    let request = create_calculate_request(input, &mutex_profile.lock().unwrap().profile);
+
+    start_search_job(app_handle, request)
+}
 
-    ret.push_str(&format!(
-        "Candidate cases count: {},\nall_loop_cases sorting elapsed: {:?}\n",
-        total_case_count, elapsed_sort
-    ));
+#[tauri::command]
+fn cmd_start_search(request: SearchRequest, app_handle: AppHandle) -> Result<u32, String> {
+    start_search_job(app_handle, request)
+}
 
-    debug!(
-        "Candidate cases calculated, start calculating: {}\n",
-        total_case_count
-    );
+/// Returns false if the job is already finished
+#[tauri::command]
+fn cmd_cancel_search(job_id: u32, mutex_jobs: tauri::State<Mutex<SearchJobs>>) -> bool {
+    let jobs = mutex_jobs.lock().unwrap();
 
-    let mut total_index = 0;
-    let mut answers = Vec::new();
-
-    for (_, case_vec) in &all_loop_tree {
-        for (real_parts, avail_slots, req_skills) in case_vec {
-            total_index += 1;
-
-            calculate_full_equip(
-                dm,
-                &req_skills,
-                &weapon_slots,
-                real_parts,
-                &avail_slots,
-                &mut answers,
-                &mut total_index,
-            );
+    match jobs.cancel_flags.get(&job_id) {
+        Some(cancel_flag) => {
+            cancel_flag.store(true, Ordering::Relaxed);
+            true
         }
+        None => false,
     }
+}
 
-    info!(
-        "All combinations size: {}, answers size: {}",
-        total_index,
-        answers.len()
-    );
+#[tauri::command]
+fn cmd_search_additional_skills(
+    request: AdditionalSkillsRequest,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<Vec<AdditionalSkill>, String> {
+    let dm = rwlock_dm.read().unwrap();
 
-    let elapsed_final = start_time.elapsed();
+    search_additional_skills(&dm, &request)
+}
 
-    let mut all_answers_length = 0;
+#[tauri::command]
+fn cmd_get_max_skill_levels(
+    request: SearchRequest,
+    rwlock_dm: tauri::State<SharedDataManager>,
+) -> Result<Option<HashMap<String, i32>>, String> {
+    let dm = rwlock_dm.read().unwrap();
 
-    for (_, deco_combs) in answers.iter() {
-        for _ in deco_combs.iter() {
-            all_answers_length += 1;
-        }
-    }
+    get_max_skill_levels(&dm, &request)
+}
 
-    ret.push_str(&format!(
-        "calculate_skillset elapsed: {:?},\nanswers length: {}\n",
-        elapsed_final, all_answers_length
+/// Data files with the bundled overrides, then the user's ones in the app data dir
+fn load_data_manager(app_dir: &Path) -> Result<(DataManager, DataReport), String> {
+    let mut override_files = OverrideFile::find_in_dir(&Path::new("./data").join(OVERRIDE_DIRNAME));
+    override_files.append(&mut OverrideFile::find_in_dir(
+        &app_dir.join(OVERRIDE_DIRNAME),
     ));
-    info!("{}", ret);
-
-    let result_equipments = answers
-        .into_iter()
-        .map(|(equipments, deco_combs)| {
-            let result_armors = equipments
-                .iter()
-                .filter_map(|armor| {
-                    if armor.part() == &ArmorPart::Talisman {
-                        return None;
-                    }
 
-                    let armor = armor.as_armor();
-
-                    let result_armor = ResultArmor {
-                        base_id: armor.original_id().clone(),
-                        is_anomaly: BaseArmor::is_anomaly_armor(armor.id()),
-                        skills: armor.skills().clone(),
-                        slots: armor.slots().clone(),
-                    };
-
-                    Some((armor.part().as_str().to_string(), result_armor))
-                })
-                .collect::<HashMap<String, ResultArmor>>();
-
-            let result_deco_combs = deco_combs
-                .iter()
-                .map(|deco_comb| ResultDecorationCombination {
-                    skills: deco_comb.combs_per_skill.clone(),
-                    slots_sum: deco_comb.sum.clone(),
-                })
-                .collect::<Vec<ResultDecorationCombination>>();
-
-            let full_equip = FullEquipments::<'a>::new(weapon_slots.clone(), equipments);
-            let talisman = full_equip.get_by_part(&ArmorPart::Talisman).as_talisman(); // TODO: without FullEquipments
-
-            let result_tali = ResultTalisman {
-                skills: talisman.skills().clone(),
-                slots: talisman.slots().clone(),
-            };
+    debug!("Override files: {:?}", override_files);
 
-            let result_equip = ResultFullEquipments {
-                armors: result_armors,
-                deco_combs: result_deco_combs,
-                talisman: result_tali,
-            };
-
-            result_equip
-        })
-        .collect::<Vec<ResultFullEquipments>>();
-
-    return (
-        ret,
-        CalculateResult {
-            full_equipments: result_equipments,
-        },
-    );
-}
+    let (dm, report) = create_data_manager_with_overrides(
+        "./data/armor.json",
+        "./data/skill.json",
+        "./data/deco.json",
+        &override_files,
+    )?;
 
-fn calculate_full_equip<'a>(
-    dm: &'a DataManager,
-    req_skills: &HashMap<String, i32>,
-    weapon_slots: &Vec<i32>,
-    real_parts: &Vec<&BoxCalcEquipment<'a>>,
-    avail_slots: &Vec<i32>,
-    answers: &mut Vec<(Vec<BoxCalcEquipment<'a>>, Vec<DecorationCombination>)>,
-    total_index: &mut i32,
-) -> i32 {
-    let mut possible_deco_combs = dm.deco_combinations.get_possible_combs(&req_skills);
-    possible_deco_combs.retain(|comb| comb.is_possible(avail_slots));
-
-    let local_result = possible_deco_combs.len() != 0;
-
-    if local_result == false {
-        return 1;
+    for issue in &report.issues {
+        info!("{}", issue);
     }
 
-    let equipments = real_parts
-        .iter()
-        .map(|part| part.clone_dyn())
-        .collect::<Vec<BoxCalcEquipment<'a>>>();
+    let name_collisions = dm.get_name_collisions();
 
-    let (all_skills, _) = FullEquipments::calculate_skills_slots(weapon_slots, &equipments);
-
-    debug!("Initial slots: {:?}", avail_slots);
-    debug!("Skill ids: {:?}", all_skills);
-
-    for local_answer in &possible_deco_combs {
-        debug!("Local answer: {:?}", local_answer);
+    for collision in &name_collisions {
+        info!(
+            "Ambiguous {} name '{}' ({}): {:?}",
+            collision.kind,
+            collision.name,
+            collision.lang.as_deref().unwrap_or("across languages"),
+            collision.ids
+        );
     }
 
     debug!(
-        "Possible slot combinations: {:?} {:?}",
-        possible_deco_combs
-            .iter()
-            .map(|comb| &comb.combs_per_skill)
-            .collect::<Vec<&HashMap<String, Vec<i32>>>>(),
-        possible_deco_combs
-            .iter()
-            .map(|comb| &comb.sum)
-            .collect::<Vec<&Vec<i32>>>()
+        "Anomaly armor count: {}, talisman count: {}",
+        dm.anomaly_armors.len(),
+        dm.talismans.len()
     );
 
-    let mut equips_by_part = HashMap::new();
-
-    for equipment in real_parts {
-        equips_by_part.insert(equipment.part().clone(), equipment);
-    }
-
-    let helm = equips_by_part[&ArmorPart::Helm];
-    let torso = equips_by_part[&ArmorPart::Torso];
-    let arm = equips_by_part[&ArmorPart::Arm];
-    let waist = equips_by_part[&ArmorPart::Waist];
-    let feet = equips_by_part[&ArmorPart::Feet];
-    let tali = equips_by_part[&ArmorPart::Talisman];
-
-    debug!(
-        "Armors ids: ({}), ({}), ({}), ({}), ({}), ({})",
-        helm.id(),
-        torso.id(),
-        arm.id(),
-        waist.id(),
-        feet.id(),
-        tali.id(),
-    );
+    Ok((dm, report))
+}
 
-    debug!(
-        "Armors names: ({}), ({}), ({}), ({}), ({})",
-        helm.as_armor().name("ko"),
-        torso.as_armor().name("ko"),
-        arm.as_armor().name("ko"),
-        waist.as_armor().name("ko"),
-        feet.as_armor().name("ko"),
-    );
+fn main() {
+    env_logger::init();
 
-    let mut real_armors = Vec::<Vec<BoxCalcEquipment<'a>>>::new();
+    let anomaly_submenu = CustomMenuItem::new("anomaly_crafting".to_string(), "Anomaly Crafting");
 
-    for equipment in real_parts {
-        let equip_id = equipment.id();
-        let part = equipment.part();
+    let data_submenu = Submenu::new("Data", Menu::new().add_item(anomaly_submenu));
 
-        let is_slot_equip = BaseArmor::is_slot_armor(equip_id);
+    let menu = Menu::new()
+        .add_native_item(MenuItem::Copy)
+        .add_submenu(data_submenu);
 
-        if is_slot_equip {
-            if part == &ArmorPart::Talisman {
-                let talis_by_slot = dm.talismans_by_slot.get(equip_id).unwrap();
+    tauri::Builder::default()
+        .manage(Mutex::new(SearchJobs::default()))
+        .setup(|app| {
+            let app_dir = app
+                .path_resolver()
+                .app_dir()
+                .unwrap_or_else(|| PathBuf::from("."));
 
-                let mut all_real_talis = Vec::new();
+            // Broken data would fail searches in confusing ways, the app doesn't start instead
+            let (dm, report) = load_data_manager(&app_dir)?;
 
-                for base_tali in talis_by_slot {
-                    let box_tali = CalcTalisman::new(base_tali).clone_dyn();
-                    all_real_talis.push(box_tali);
-                }
+            app.manage(RwLock::new(Arc::new(dm)));
+            app.manage(report);
 
-                real_armors.push(all_real_talis);
-            } else {
-                let armors_by_slot = &dm.armors_by_slot[part][equip_id];
+            let filename = app_dir.join(PROFILE_FILENAME);
+            let profile = HunterProfile::load(&filename);
 
-                let mut all_real_armors = Vec::<BoxCalcEquipment<'a>>::new();
+            debug!("Profile loaded from {:?}: {:?}", filename, profile);
 
-                for base_armor in armors_by_slot {
-                    let anomaly_base = dm.get_anomaly_armor(base_armor.id());
+            app.manage(Mutex::new(ProfileState { filename, profile }));
 
-                    let calc_armor = match anomaly_base {
-                        Some(anomaly_armor) => {
-                            CalcArmor::<'a>::new_anomaly(&anomaly_armor.affected, base_armor)
-                        }
-                        None => CalcArmor::<'a>::new_anomaly(base_armor, base_armor),
-                    };
+            let filename = app_dir.join(LOADOUTS_FILENAME);
+            let store = match LoadoutStore::load(&filename) {
+                Ok(store) => store,
+                Err(message) => {
+                    // Kept aside so saving new loadouts doesn't overwrite it
+                    let invalid_filename = filename.with_extension("json.invalid");
+                    info!("{}, moved to {:?}", message, invalid_filename);
 
-                    let box_armor = calc_armor.clone_dyn();
+                    if let Err(err) = std::fs::rename(&filename, &invalid_filename) {
+                        info!("Failed to move invalid loadouts: {}", err);
+                    }
 
-                    all_real_armors.push(box_armor);
+                    LoadoutStore::default()
                 }
+            };
 
-                real_armors.push(all_real_armors);
-            }
-        } else {
-            real_armors.push(vec![equipment.clone_dyn()]);
-        }
-    }
-
-    let mut answers_equip = Vec::new();
-
-    for (a0, a1, a2, a3, a4, a5) in iproduct!(
-        &real_armors[0],
-        &real_armors[1],
-        &real_armors[2],
-        &real_armors[3],
-        &real_armors[4],
-        &real_armors[5]
-    ) {
-        let equipments = vec![
-            a0.clone(),
-            a1.clone(),
-            a2.clone(),
-            a3.clone(),
-            a4.clone(),
-            a5.clone(),
-        ];
-
-        answers_equip.push(equipments);
-    }
-
-    for equip in answers_equip.into_iter() {
-        answers.push((equip, possible_deco_combs.clone()));
-    }
-
-    info!(
-        "Answers length: {}, total_index: {}\n",
-        answers.len(),
-        total_index
-    );
-
-    return 0;
-}
-
-fn create_data_manager(
-    armors_filename: &str,
-    skills_filename: &str,
-    decos_filename: &str,
-) -> DataManager {
-    let armors_vec = parse_data::<BaseArmor>(armors_filename);
-    let skills_vec = parse_data::<Skill>(skills_filename);
-    let decos_vec = parse_data::<Decoration>(decos_filename);
-
-    let mut armors = HashMap::<String, BaseArmor>::new();
-    let mut skills = HashMap::<String, Skill>::new();
-    let mut decos = HashMap::<String, Decoration>::new();
-
-    for armor in armors_vec {
-        armors.insert(armor.id().clone(), armor);
-    }
-
-    for skill in skills_vec {
-        skills.insert(skill.id.clone(), skill);
-    }
-
-    for deco in decos_vec {
-        decos.insert(deco.id.clone(), deco);
-    }
-
-    let dm = DataManager::new(armors, skills, decos);
-
-    dm
-}
-
-fn main() {
-    env_logger::init();
-
-    let dm = create_data_manager("./data/armor.json", "./data/skill.json", "./data/deco.json");
-
-    debug!(
-        "Anomaly armor count: {}, talisman count: {}",
-        dm.anomaly_armors.len(),
-        dm.talismans.len()
-    );
-
-    let anomaly_submenu = CustomMenuItem::new("anomaly_crafting".to_string(), "Anomaly Crafting");
-
-    let data_submenu = Submenu::new("Data", Menu::new().add_item(anomaly_submenu));
+            app.manage(Mutex::new(LoadoutState { filename, store }));
 
-    let menu = Menu::new()
-        .add_native_item(MenuItem::Copy)
-        .add_submenu(data_submenu);
-
-    tauri::Builder::default()
-        .manage(Mutex::new(dm))
+            Ok(())
+        })
         .menu(menu)
         .on_menu_event(|event| match event.menu_item_id() {
             "anomaly_crafting" => {
@@ -1359,9 +641,31 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             cmd_parse_anomaly,
             cmd_parse_talisman,
+            cmd_get_talismans,
+            cmd_add_talisman,
+            cmd_edit_talisman,
+            cmd_delete_talisman,
+            cmd_parse_armor_inventory,
+            cmd_get_armor_inventory,
+            cmd_set_armor_ownership,
+            cmd_parse_deco_inventory,
+            cmd_get_name_collisions,
+            cmd_get_data_report,
             cmd_get_skill_names,
             cmd_get_armor_names,
-            cmd_calculate_skillset
+            cmd_get_profile,
+            cmd_set_profile,
+            cmd_save_loadout,
+            cmd_get_loadouts,
+            cmd_delete_loadout,
+            cmd_export_result,
+            cmd_encode_build,
+            cmd_decode_build,
+            cmd_start_calculate_skillset,
+            cmd_start_search,
+            cmd_cancel_search,
+            cmd_search_additional_skills,
+            cmd_get_max_skill_levels
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");